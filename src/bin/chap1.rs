@@ -12,7 +12,7 @@ fn main() {
             let ir = (255.99 * r) as u32;
             let ig = (255.99 * g) as u32;
             let ib = (255.99 * 0.2) as u32;
-            write!(stdout, "{} {} {}\n", ir, ig, ib).unwrap();
+            writeln!(stdout, "{} {} {}", ir, ig, ib).unwrap();
         }
     }
 }
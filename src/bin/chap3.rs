@@ -21,12 +21,16 @@ fn main() {
         for i in 0..nx {
             let u = i as f32 / nx as f32;
             let v = j as f32 / ny as f32;
-            let ray = Ray::new(origin, lower_left_corner + horizontal * u + vertical * v);
+            let ray = Ray::new(
+                origin,
+                lower_left_corner + horizontal * u + vertical * v,
+                0.,
+            );
             let col = color(ray);
             let ir = (255.99 * col.x) as u32;
             let ig = (255.99 * col.y) as u32;
             let ib = (255.99 * col.z) as u32;
-            write!(stdout, "{} {} {}\n", ir, ig, ib).unwrap();
+            writeln!(stdout, "{} {} {}", ir, ig, ib).unwrap();
         }
     }
 }
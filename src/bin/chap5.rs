@@ -1,10 +1,13 @@
 use ray_tracer::hitable;
+use ray_tracer::material;
 use ray_tracer::ray::Ray;
+use ray_tracer::sampler::Sampler;
 use ray_tracer::vec3::Vec3;
 use std::io::{self, Write};
+use std::sync::Arc;
 
-fn color<'a>(ray: Ray, world: &[&'a dyn hitable::Hitable]) -> Vec3 {
-    if let Some(rec) = hitable::hit(world, ray, 0., std::f32::MAX) {
+fn color(ray: Ray, world: &dyn hitable::Hitable, sampler: &mut Sampler) -> Vec3 {
+    if let Some(rec) = world.hit(ray, 0., f32::MAX, sampler) {
         return (rec.normal + 1.) * 0.5;
     }
     let unit_direction = ray.direction().make_unit_vector();
@@ -21,20 +24,33 @@ fn main() {
     let horizontal = Vec3::new(4., 0., 0.);
     let vertical = Vec3::new(0., 2., 0.);
     let origin = Vec3::new(0., 0., 0.);
-    let s1 = hitable::Sphere::new(Vec3::new(0., 0., -1.), 0.5);
-    let s2 = hitable::Sphere::new(Vec3::new(0., -100.5, -1.), 100.);
-    let world: Vec<&dyn hitable::Hitable> = vec![&s1, &s2];
+    let mut sampler = Sampler::new(0);
+    let mut world = hitable::HitableList::new();
+    world.add(Arc::new(hitable::Sphere::new(
+        Vec3::new(0., 0., -1.),
+        0.5,
+        material::Lambertian::new(Vec3::new(0.5, 0.5, 0.5)),
+    )));
+    world.add(Arc::new(hitable::Sphere::new(
+        Vec3::new(0., -100.5, -1.),
+        100.,
+        material::Lambertian::new(Vec3::new(0.5, 0.5, 0.5)),
+    )));
 
     for j in (0..ny).rev() {
         for i in 0..nx {
             let u = i as f32 / nx as f32;
             let v = j as f32 / ny as f32;
-            let ray = Ray::new(origin, lower_left_corner + horizontal * u + vertical * v);
-            let col = color(ray, &world);
+            let ray = Ray::new(
+                origin,
+                lower_left_corner + horizontal * u + vertical * v,
+                0.,
+            );
+            let col = color(ray, &world, &mut sampler);
             let ir = (255.99 * col.x) as u32;
             let ig = (255.99 * col.y) as u32;
             let ib = (255.99 * col.z) as u32;
-            write!(stdout, "{} {} {}\n", ir, ig, ib).unwrap();
+            writeln!(stdout, "{} {} {}", ir, ig, ib).unwrap();
         }
     }
 }
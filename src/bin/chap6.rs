@@ -1,12 +1,14 @@
-use rand::distributions::{Distribution, Uniform};
 use ray_tracer::camera::Camera;
 use ray_tracer::hitable;
+use ray_tracer::material;
 use ray_tracer::ray::Ray;
+use ray_tracer::sampler::Sampler;
 use ray_tracer::vec3::Vec3;
 use std::io::{self, Write};
+use std::sync::Arc;
 
-fn color(ray: Ray, world: &[&dyn hitable::Hitable]) -> Vec3 {
-    if let Some(rec) = hitable::hit(world, ray, 0., std::f32::MAX) {
+fn color(ray: Ray, world: &dyn hitable::Hitable, sampler: &mut Sampler) -> Vec3 {
+    if let Some(rec) = world.hit(ray, 0., f32::MAX, sampler) {
         return (rec.normal + 1.) * 0.5;
     }
     let unit_direction = ray.direction().make_unit_vector();
@@ -20,28 +22,44 @@ fn main() {
     let ny = 100;
     let ns = 100;
     write!(stdout, "P3\n{} {}\n255\n", nx, ny).unwrap();
-    let cam = Camera::new();
+    let cam = Camera::new(
+        Vec3::new(0., 0., 0.),
+        Vec3::new(0., 0., -1.),
+        Vec3::new(0., 1., 0.),
+        90.,
+        nx as f32 / ny as f32,
+        0.,
+        1.,
+    );
 
-    let s1 = hitable::Sphere::new(Vec3::new(0., 0., -1.), 0.5);
-    let s2 = hitable::Sphere::new(Vec3::new(0., -100.5, -1.), 100.);
-    let world: Vec<&dyn hitable::Hitable> = vec![&s1, &s2];
-    let between = Uniform::new(0., 1.);
-    let mut rng = rand::thread_rng();
+    let mut world = hitable::HitableList::new();
+    world.add(Arc::new(hitable::Sphere::new(
+        Vec3::new(0., 0., -1.),
+        0.5,
+        material::Lambertian::new(Vec3::new(0.5, 0.5, 0.5)),
+    )));
+    world.add(Arc::new(hitable::Sphere::new(
+        Vec3::new(0., -100.5, -1.),
+        100.,
+        material::Lambertian::new(Vec3::new(0.5, 0.5, 0.5)),
+    )));
+    let mut sampler = Sampler::new(0);
     for j in (0..ny).rev() {
         for i in 0..nx {
             let mut col = Vec3::new(0., 0., 0.);
             for _ in 0..ns {
                 let ray = cam.get_ray(
-                    (i as f32 + between.sample(&mut rng)) / nx as f32,
-                    (j as f32 + between.sample(&mut rng)) / ny as f32,
+                    (i as f32 + sampler.next_f32()) / nx as f32,
+                    (j as f32 + sampler.next_f32()) / ny as f32,
+                    &mut sampler,
                 );
-                col += color(ray, &world);
+                col += color(ray, &world, &mut sampler);
             }
             col /= ns as f32;
             let ir = (255.99 * col.x) as u32;
             let ig = (255.99 * col.y) as u32;
             let ib = (255.99 * col.z) as u32;
-            write!(stdout, "{} {} {}\n", ir, ig, ib).unwrap();
+            writeln!(stdout, "{} {} {}", ir, ig, ib).unwrap();
         }
     }
 }
@@ -0,0 +1,147 @@
+use ray_tracer::image::ImageFormat;
+use ray_tracer::render;
+use ray_tracer::scene;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
+use std::str::FromStr;
+use std::time::Instant;
+
+const USAGE: &str = "\
+usage: raytrace <scene> [options]
+
+Renders a scene file and saves the image, in the format given by the
+extension of the output path (png, ppm or pfm).
+
+options:
+  -o, --output <path>    output image (default: out.png)
+  -w, --width <n>        image width in pixels
+  -h, --height <n>       image height in pixels
+  -s, --samples <n>      rays per pixel
+  -d, --max-depth <n>    maximum number of bounces per ray
+  -t, --threads <n>      number of worker threads (default: all cores)
+      --seed <n>         random seed
+      --help             show this message
+
+Options override the settings of the scene file.";
+
+/// Command line options. `None` keeps the value of the scene file.
+#[derive(Default)]
+struct Options {
+    scene: String,
+    output: String,
+    width: Option<usize>,
+    height: Option<usize>,
+    samples: Option<usize>,
+    max_depth: Option<usize>,
+    threads: Option<usize>,
+    seed: Option<u64>,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        output: "out.png".to_string(),
+        ..Options::default()
+    };
+    let mut scene = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("option `{}` needs a value", arg))
+        };
+        match arg.as_str() {
+            "--help" => return Ok(None),
+            "-o" | "--output" => options.output = value()?,
+            "-w" | "--width" => options.width = Some(number(&arg, &value()?)?),
+            "-h" | "--height" => options.height = Some(number(&arg, &value()?)?),
+            "-s" | "--samples" => options.samples = Some(number(&arg, &value()?)?),
+            "-d" | "--max-depth" => options.max_depth = Some(number(&arg, &value()?)?),
+            "-t" | "--threads" => options.threads = Some(number(&arg, &value()?)?),
+            "--seed" => options.seed = Some(number(&arg, &value()?)?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{}`", arg)),
+            _ if scene.is_none() => scene = Some(arg),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    options.scene = scene.ok_or("no scene file given")?;
+    Ok(Some(options))
+}
+
+fn number<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("option `{}` needs a number, got `{}`", option, value))
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let mut scene =
+        scene::load(&options.scene).map_err(|err| format!("{}: {}", options.scene, err))?;
+    let settings = &mut scene.settings;
+    settings.width = options.width.unwrap_or(settings.width);
+    settings.height = options.height.unwrap_or(settings.height);
+    settings.samples = options.samples.unwrap_or(settings.samples);
+    settings.max_depth = options.max_depth.unwrap_or(settings.max_depth);
+    settings.threads = options.threads.unwrap_or(settings.threads);
+    settings.seed = options.seed.unwrap_or(settings.seed);
+    if settings.width == 0 || settings.height == 0 || settings.samples == 0 || settings.threads == 0
+    {
+        return Err("width, height, samples and threads must be positive".to_string());
+    }
+
+    // opened now rather than failing to save after a long render
+    let format = ImageFormat::from_path(&options.output).map_err(|_| {
+        format!(
+            "cannot write {}: the output must be a .png, .ppm or .pfm file",
+            options.output
+        )
+    })?;
+    let file = File::create(&options.output)
+        .map_err(|err| format!("cannot write {}: {}", options.output, err))?;
+
+    let settings = scene.settings;
+    eprintln!(
+        "rendering {} at {}x{}, {} samples per pixel, {} threads",
+        options.scene, settings.width, settings.height, settings.samples, settings.threads
+    );
+    let start = Instant::now();
+    let image = render::render_with_progress(&*scene.world, &scene.camera(), &settings, |rows| {
+        eprint!(
+            "\r{:3}% ({}/{} rows)",
+            rows * 100 / settings.height,
+            rows,
+            settings.height
+        );
+        let _ = io::stderr().flush();
+    });
+    eprintln!();
+    let mut w = BufWriter::new(file);
+    image
+        .write(&mut w, format)
+        .and_then(|()| w.flush())
+        .map_err(|err| format!("cannot write {}: {}", options.output, err))?;
+    eprintln!(
+        "wrote {} in {:.1}s",
+        options.output,
+        start.elapsed().as_secs_f32()
+    );
+    Ok(())
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("raytrace: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(message) = run(options) {
+        eprintln!("raytrace: {}", message);
+        process::exit(1);
+    }
+}
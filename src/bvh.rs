@@ -0,0 +1,96 @@
+use crate::aabb::Aabb;
+use crate::hitable::{HitResult, Hitable};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use std::sync::Arc;
+
+/// Bounding volume hierarchy. Children are split at the median along the
+/// longest axis of their centroids.
+pub struct BvhNode {
+    left: Arc<dyn Hitable>,
+    /// `None` in a leaf holding a single object, so that it is tested once.
+    right: Option<Arc<dyn Hitable>>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    /// # Panics
+    ///
+    /// Panics if `objects` is empty or one of them has no bounding box.
+    pub fn new(objects: Vec<Arc<dyn Hitable>>) -> Self {
+        assert!(
+            !objects.is_empty(),
+            "BvhNode::new needs at least one object"
+        );
+        let items = objects
+            .into_iter()
+            .map(|o| {
+                let bbox = o
+                    .bounding_box()
+                    .expect("BvhNode::new needs objects with a bounding box");
+                (bbox, o)
+            })
+            .collect();
+        Self::build(items)
+    }
+
+    /// Builds the tree from objects paired with their boxes, so that no box
+    /// is computed more than once.
+    fn build(mut items: Vec<(Aabb, Arc<dyn Hitable>)>) -> Self {
+        let bbox = items[1..]
+            .iter()
+            .fold(items[0].0, |acc, (b, _)| acc.surrounding(*b));
+
+        let (left, right) = match items.len() {
+            1 => (items[0].1.clone(), None),
+            2 => (items[0].1.clone(), Some(items[1].1.clone())),
+            n => {
+                let axis = longest_axis(items.iter().map(|(b, _)| b));
+                items.sort_by(|(a, _), (b, _)| {
+                    a.centroid()[axis]
+                        .partial_cmp(&b.centroid()[axis])
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                let rest = items.split_off(n / 2);
+                (
+                    Arc::new(BvhNode::build(items)) as Arc<dyn Hitable>,
+                    Some(Arc::new(BvhNode::build(rest)) as Arc<dyn Hitable>),
+                )
+            }
+        };
+        BvhNode { left, right, bbox }
+    }
+}
+
+/// Axis along which the centroids of `boxes` spread the most.
+fn longest_axis<'a, I: Iterator<Item = &'a Aabb>>(mut boxes: I) -> usize {
+    let point = |b: &Aabb| Aabb::new(b.centroid(), b.centroid());
+    let first = point(boxes.next().expect("longest_axis needs a box"));
+    let bounds = boxes.fold(first, |acc, b| acc.surrounding(point(b)));
+    let extent = bounds.max() - bounds.min();
+    if extent.x > extent.y && extent.x > extent.z {
+        0
+    } else if extent.y > extent.z {
+        1
+    } else {
+        2
+    }
+}
+
+impl Hitable for BvhNode {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, sampler: &mut Sampler) -> HitResult<'_> {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return None;
+        }
+        let left = self.left.hit(r, t_min, t_max, sampler);
+        let right = match &self.right {
+            Some(right) => right.hit(r, t_min, left.map_or(t_max, |rec| rec.t), sampler),
+            None => None,
+        };
+        right.or(left)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
@@ -1,27 +1,71 @@
+use crate::material::random_in_unit_disk;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use crate::vec3::Vec3;
+use std::f32::consts::PI;
 
 pub struct Camera {
     origin: Vec3,
     lower_left_corner: Vec3,
     horizontal: Vec3,
     vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    lens_radius: f32,
+    time0: f32,
+    time1: f32,
 }
 
 impl Camera {
-    pub fn new() -> Self {
+    /// `vfov` is the top to bottom field of view in degrees.
+    /// Objects at `focus_dist` from `look_from` are in perfect focus.
+    pub fn new(
+        look_from: Vec3,
+        look_at: Vec3,
+        vup: Vec3,
+        vfov: f32,
+        aspect: f32,
+        aperture: f32,
+        focus_dist: f32,
+    ) -> Self {
+        let theta = vfov * PI / 180.;
+        let half_height = (theta / 2.).tan();
+        let half_width = aspect * half_height;
+        let w = (look_from - look_at).make_unit_vector();
+        let u = vup.cross(w).make_unit_vector();
+        let v = w.cross(u);
         Camera {
-            origin: Vec3::new(0., 0., 0.),
-            lower_left_corner: Vec3::new(-2., -1., -1.),
-            horizontal: Vec3::new(4., 0., 0.),
-            vertical: Vec3::new(0., 2., 0.),
+            origin: look_from,
+            lower_left_corner: look_from
+                - u * (half_width * focus_dist)
+                - v * (half_height * focus_dist)
+                - w * focus_dist,
+            horizontal: u * (2. * half_width * focus_dist),
+            vertical: v * (2. * half_height * focus_dist),
+            u,
+            v,
+            lens_radius: aperture / 2.,
+            time0: 0.,
+            time1: 0.,
         }
     }
 
-    pub fn get_ray(&self, u: f32, v: f32) -> Ray {
+    /// Keeps the shutter open from `time0` to `time1`, sending each ray at a
+    /// random moment in between.
+    pub fn with_shutter(mut self, time0: f32, time1: f32) -> Self {
+        self.time0 = time0;
+        self.time1 = time1;
+        self
+    }
+
+    pub fn get_ray(&self, s: f32, t: f32, sampler: &mut Sampler) -> Ray {
+        let rd = random_in_unit_disk(sampler) * self.lens_radius;
+        let offset = self.u * rd.x + self.v * rd.y;
+        let time = self.time0 + sampler.next_f32() * (self.time1 - self.time0);
         Ray::new(
-            self.origin,
-            self.lower_left_corner + self.horizontal * u + self.vertical * v - self.origin,
+            self.origin + offset,
+            self.lower_left_corner + self.horizontal * s + self.vertical * t - self.origin - offset,
+            time,
         )
     }
 }
@@ -1,18 +1,59 @@
-use crate::material::{Material, ScatterResult};
+use crate::aabb::Aabb;
+use crate::material::Material;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
 use crate::vec3::Vec3;
+use std::f32::consts::PI;
+use std::sync::Arc;
 
-#[derive(Clone, Copy, Debug)]
-pub struct HitRecord {
+#[derive(Clone, Copy)]
+pub struct HitRecord<'a> {
     pub t: f32,
     pub p: Vec3,
+    /// Always points against the incident ray.
     pub normal: Vec3,
+    /// Whether the ray hit the surface from the outside.
+    pub front_face: bool,
+    /// Surface coordinates of the hit point.
+    pub u: f32,
+    pub v: f32,
+    pub mat: &'a dyn Material,
 }
 
-type HitResult = Option<(HitRecord, ScatterResult)>;
+impl<'a> HitRecord<'a> {
+    pub fn new(r: Ray, t: f32, outward_normal: Vec3, mat: &'a dyn Material) -> Self {
+        let mut rec = HitRecord {
+            t,
+            p: r.point_at_parameter(t),
+            normal: outward_normal,
+            front_face: true,
+            u: 0.,
+            v: 0.,
+            mat,
+        };
+        rec.set_face_normal(r, outward_normal);
+        rec
+    }
+
+    pub fn set_face_normal(&mut self, r: Ray, outward_normal: Vec3) {
+        self.front_face = r.direction().dot(outward_normal) < 0.;
+        self.normal = if self.front_face {
+            outward_normal
+        } else {
+            -outward_normal
+        };
+    }
+}
+
+pub type HitResult<'a> = Option<HitRecord<'a>>;
+
+pub trait Hitable: Send + Sync {
+    /// `sampler` is for objects that are hit at random, like participating
+    /// media.
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, sampler: &mut Sampler) -> HitResult<'_>;
 
-pub trait Hitable {
-    fn hit(&self, r: Ray, t_min: f32, t_max: f32) -> HitResult;
+    /// Box enclosing the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
 }
 
 pub struct Sphere<T: Material> {
@@ -32,46 +73,156 @@ impl<T: Material> Sphere<T> {
 }
 
 impl<T: Material> Hitable for Sphere<T> {
-    fn hit(&self, r: Ray, t_min: f32, t_max: f32) -> HitResult {
-        let oc = r.origin() - self.center;
-        let a = r.direction().dot(r.direction());
-        let b = r.direction().dot(oc);
-        let c = oc.dot(oc) - self.radius * self.radius;
-        let discriminant = b * b - a * c;
-        if discriminant > 0. {
-            let temp = (-b - discriminant.sqrt()) / a;
-            if temp < t_max && temp > t_min {
-                let p = r.point_at_parameter(temp);
-                let rec = HitRecord {
-                    t: temp,
-                    normal: (p - self.center) / self.radius,
-                    p,
-                };
-                return Some((rec, self.mat.scatter(r, rec)));
-            }
-            let temp = (-b + discriminant.sqrt()) / a;
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, _: &mut Sampler) -> HitResult<'_> {
+        hit_sphere(self.center, self.radius, &self.mat, r, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(sphere_box(self.center, self.radius))
+    }
+}
+
+/// Sphere whose center moves linearly from `center0` at `time0` to `center1`
+/// at `time1`.
+pub struct MovingSphere<T: Material> {
+    center0: Vec3,
+    center1: Vec3,
+    time0: f32,
+    time1: f32,
+    radius: f32,
+    mat: T,
+}
+
+impl<T: Material> MovingSphere<T> {
+    pub fn new(center0: Vec3, center1: Vec3, time0: f32, time1: f32, radius: f32, mat: T) -> Self {
+        Self {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            mat,
+        }
+    }
+
+    pub fn center(&self, time: f32) -> Vec3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        self.center0
+            + (self.center1 - self.center0) * ((time - self.time0) / (self.time1 - self.time0))
+    }
+}
+
+impl<T: Material> Hitable for MovingSphere<T> {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, _: &mut Sampler) -> HitResult<'_> {
+        hit_sphere(
+            self.center(r.time()),
+            self.radius,
+            &self.mat,
+            r,
+            t_min,
+            t_max,
+        )
+    }
+
+    /// Covers the motion between `time0` and `time1` only.
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(
+            sphere_box(self.center0, self.radius)
+                .surrounding(sphere_box(self.center1, self.radius)),
+        )
+    }
+}
+
+fn hit_sphere(
+    center: Vec3,
+    radius: f32,
+    mat: &dyn Material,
+    r: Ray,
+    t_min: f32,
+    t_max: f32,
+) -> HitResult<'_> {
+    let oc = r.origin() - center;
+    let a = r.direction().dot(r.direction());
+    let b = r.direction().dot(oc);
+    let c = oc.dot(oc) - radius * radius;
+    let discriminant = b * b - a * c;
+    if discriminant > 0. {
+        for temp in [
+            (-b - discriminant.sqrt()) / a,
+            (-b + discriminant.sqrt()) / a,
+        ] {
             if temp < t_max && temp > t_min {
                 let p = r.point_at_parameter(temp);
-                let rec = HitRecord {
-                    t: temp,
-                    normal: (p - self.center) / self.radius,
-                    p,
-                };
-                return Some((rec, self.mat.scatter(r, rec)));
+                let mut rec = HitRecord::new(r, temp, (p - center) / radius, mat);
+                let (u, v) = sphere_uv((p - center) / radius.abs());
+                rec.u = u;
+                rec.v = v;
+                return Some(rec);
             }
         }
-        return None;
     }
+    None
+}
+
+/// Maps a point on the unit sphere to `u` around the y axis, starting from -x,
+/// and `v` from the bottom pole to the top one.
+fn sphere_uv(p: Vec3) -> (f32, f32) {
+    let phi = (-p.z).atan2(p.x) + PI;
+    let theta = (-p.y).acos();
+    (phi / (2. * PI), theta / PI)
+}
+
+fn sphere_box(center: Vec3, radius: f32) -> Aabb {
+    let r = Vec3::new(radius.abs(), radius.abs(), radius.abs());
+    Aabb::new(center - r, center + r)
+}
+
+/// Owning group of objects. Only the closest hit among them is returned, so
+/// its material is the only one the caller scatters.
+#[derive(Clone, Default)]
+pub struct HitableList {
+    objects: Vec<Arc<dyn Hitable>>,
 }
 
-pub fn hit(hitables: &[&dyn Hitable], r: Ray, t_min: f32, t_max: f32) -> ScatterResult {
-    let mut res = None;
-    let mut closest_so_far = t_max;
-    for hitable in hitables {
-        if let Some((rec, scatter)) = hitable.hit(r, t_min, closest_so_far) {
-            closest_so_far = rec.t;
-            res = scatter;
+impl HitableList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, object: Arc<dyn Hitable>) {
+        self.objects.push(object);
+    }
+
+    pub fn objects(&self) -> &[Arc<dyn Hitable>] {
+        &self.objects
+    }
+}
+
+impl From<Vec<Arc<dyn Hitable>>> for HitableList {
+    fn from(objects: Vec<Arc<dyn Hitable>>) -> Self {
+        HitableList { objects }
+    }
+}
+
+impl Hitable for HitableList {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, sampler: &mut Sampler) -> HitResult<'_> {
+        let mut res = None;
+        let mut closest_so_far = t_max;
+        for object in &self.objects {
+            if let Some(rec) = object.hit(r, t_min, closest_so_far, sampler) {
+                closest_so_far = rec.t;
+                res = Some(rec);
+            }
         }
+        res
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (first, rest) = self.objects.split_first()?;
+        rest.iter().try_fold(first.bounding_box()?, |acc, object| {
+            Some(acc.surrounding(object.bounding_box()?))
+        })
     }
-    res
 }
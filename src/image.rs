@@ -0,0 +1,437 @@
+use crate::png;
+use crate::vec3::Vec3;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(PathBuf, io::Error),
+    /// The file is not an image in a supported format, or is corrupt.
+    Format(PathBuf, String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(path, err) => write!(f, "cannot read {}: {}", path.display(), err),
+            ImageError::Format(path, message) => {
+                write!(f, "cannot decode {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageError::Io(_, err) => Some(err),
+            ImageError::Format(..) => None,
+        }
+    }
+}
+
+/// Linear radiance per pixel, stored row by row from the top of the image.
+#[derive(Clone, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>,
+}
+
+impl Image {
+    /// Creates a black image.
+    pub fn new(width: usize, height: usize) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![Vec3::new(0., 0., 0.); width * height],
+        }
+    }
+
+    /// Loads a PNG, PPM (P3 or P6) or PFM file, recognized by its contents.
+    /// 8-bit formats are taken to be sRGB encoded and converted to linear.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ImageError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|err| ImageError::Io(path.to_path_buf(), err))?;
+        let image = if data.starts_with(b"\x89PNG") {
+            png::read_rgb(&data).map(|(width, height, rgb)| Self::from_srgb(width, height, &rgb))
+        } else if data.starts_with(b"P3") || data.starts_with(b"P6") {
+            read_ppm(&data)
+        } else if data.starts_with(b"PF") || data.starts_with(b"Pf") {
+            read_pfm(&data)
+        } else {
+            Err("unknown image format".to_string())
+        };
+        let image = image.and_then(|image| {
+            if image.width.checked_mul(image.height) == Some(image.pixels.len()) {
+                Ok(image)
+            } else {
+                Err("pixel count does not match the image size".to_string())
+            }
+        });
+        image.map_err(|message| ImageError::Format(path.to_path_buf(), message))
+    }
+
+    /// Builds an image from interleaved sRGB encoded values in [0, 1].
+    fn from_srgb(width: usize, height: usize, rgb: &[f32]) -> Self {
+        let pixels = rgb
+            .chunks(3)
+            .map(|c| {
+                Vec3::new(
+                    srgb_to_linear(c[0]),
+                    srgb_to_linear(c[1]),
+                    srgb_to_linear(c[2]),
+                )
+            })
+            .collect();
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// `y` counts from the top row.
+    pub fn get(&self, x: usize, y: usize) -> Vec3 {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, col: Vec3) {
+        let i = self.index(x, y);
+        self.pixels[i] = col;
+    }
+
+    /// Adds `col` to the radiance already stored at the pixel.
+    pub fn accumulate(&mut self, x: usize, y: usize, col: Vec3) {
+        let i = self.index(x, y);
+        self.pixels[i] += col;
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel ({}, {}) out of bounds",
+            x,
+            y
+        );
+        y * self.width + x
+    }
+
+    /// Encodes the image as interleaved 8-bit sRGB, clamping radiance to [0, 1].
+    pub fn to_srgb8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|col| [col.x, col.y, col.z])
+            .map(|c| (linear_to_srgb(c.clamp(0., 1.)) * 255. + 0.5) as u8)
+            .collect()
+    }
+
+    /// Writes an ASCII PPM (P3).
+    pub fn write_ppm<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "P3\n{} {}\n255", self.width, self.height)?;
+        for rgb in self.to_srgb8().chunks(3) {
+            writeln!(w, "{} {} {}", rgb[0], rgb[1], rgb[2])?;
+        }
+        Ok(())
+    }
+
+    /// Writes a binary PPM (P6).
+    pub fn write_ppm_binary<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.to_srgb8())
+    }
+
+    /// Writes a little-endian PFM keeping the unclamped linear radiance.
+    pub fn write_pfm<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.width == 0 || self.height == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot encode a {}x{} PFM", self.width, self.height),
+            ));
+        }
+        write!(w, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+        // PFM stores the bottom row first
+        for row in self.pixels.chunks(self.width).rev() {
+            for col in row {
+                for c in &[col.x, col.y, col.z] {
+                    w.write_all(&c.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes an 8-bit RGB PNG.
+    pub fn write_png<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        png::write_rgb8(w, self.width, self.height, &self.to_srgb8())
+    }
+
+    /// Writes the image in `format`.
+    pub fn write<W: Write>(&self, w: &mut W, format: ImageFormat) -> io::Result<()> {
+        match format {
+            ImageFormat::Png => self.write_png(w),
+            ImageFormat::Ppm => self.write_ppm_binary(w),
+            ImageFormat::Pfm => self.write_pfm(w),
+        }
+    }
+
+    /// Saves the image in the format given by the extension of `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path)?;
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write(&mut w, format)?;
+        w.flush()
+    }
+}
+
+/// Formats images can be saved in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    /// Binary PPM (P6).
+    Ppm,
+    Pfm,
+}
+
+impl ImageFormat {
+    /// Picks the format from the extension of `path`: `png`, `ppm` or `pfm`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("png") => Ok(ImageFormat::Png),
+            Some("ppm") => Ok(ImageFormat::Ppm),
+            Some("pfm") => Ok(ImageFormat::Pfm),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported image format: {}", path.display()),
+            )),
+        }
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Splits off the next whitespace separated header token of a PPM or PFM,
+/// skipping `#` comments. The single whitespace byte after it is consumed too.
+fn header_token<'a>(data: &mut &'a [u8]) -> Result<&'a str, String> {
+    loop {
+        let start = data
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .ok_or("truncated header")?;
+        *data = &data[start..];
+        if data[0] != b'#' {
+            break;
+        }
+        let end = data.iter().position(|&b| b == b'\n').unwrap_or(data.len());
+        *data = &data[end..];
+    }
+    let end = data
+        .iter()
+        .position(|b| b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    let token = std::str::from_utf8(&data[..end]).map_err(|_| "malformed header")?;
+    *data = &data[(end + 1).min(data.len())..];
+    Ok(token)
+}
+
+fn header_number<T: std::str::FromStr>(data: &mut &[u8]) -> Result<T, String> {
+    let token = header_token(data)?;
+    token
+        .parse()
+        .map_err(|_| format!("invalid number `{}` in header", token))
+}
+
+fn header_size(data: &mut &[u8]) -> Result<(usize, usize), String> {
+    let width: usize = header_number(data)?;
+    let height: usize = header_number(data)?;
+    if width == 0 || height == 0 {
+        return Err("image has no pixels".to_string());
+    }
+    Ok((width, height))
+}
+
+/// Number of values in the pixel data, checked so that a corrupt header
+/// cannot overflow it.
+fn value_count(width: usize, height: usize, channels: usize) -> Result<usize, String> {
+    width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels))
+        .ok_or_else(|| "image too large".to_string())
+}
+
+fn read_ppm(mut data: &[u8]) -> Result<Image, String> {
+    let magic = header_token(&mut data)?;
+    let (width, height) = header_size(&mut data)?;
+    let max: u32 = header_number(&mut data)?;
+    if max == 0 || max > 65535 {
+        return Err(format!("invalid maximum value {}", max));
+    }
+    let count = value_count(width, height, 3)?;
+    let values: Vec<u32> = if magic == "P3" {
+        let text = std::str::from_utf8(data).map_err(|_| "malformed pixel data")?;
+        text.split_whitespace()
+            .take(count)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("invalid pixel value `{}`", v))
+            })
+            .collect::<Result<_, _>>()?
+    } else if max < 256 {
+        data.iter().take(count).map(|&b| u32::from(b)).collect()
+    } else {
+        data.chunks_exact(2)
+            .take(count)
+            .map(|b| u32::from(u16::from_be_bytes([b[0], b[1]])))
+            .collect()
+    };
+    if values.len() < count {
+        return Err("truncated pixel data".to_string());
+    }
+    let rgb: Vec<f32> = values
+        .iter()
+        .map(|&v| (v.min(max) as f32) / max as f32)
+        .collect();
+    Ok(Image::from_srgb(width, height, &rgb))
+}
+
+fn read_pfm(mut data: &[u8]) -> Result<Image, String> {
+    let channels = if header_token(&mut data)? == "PF" {
+        3
+    } else {
+        1
+    };
+    let (width, height) = header_size(&mut data)?;
+    let scale: f32 = header_number(&mut data)?;
+    let count = value_count(width, height, channels)?;
+    if data.len() / 4 < count {
+        return Err("truncated pixel data".to_string());
+    }
+    let values: Vec<f32> = data
+        .chunks_exact(4)
+        .take(count)
+        .map(|b| {
+            let b = [b[0], b[1], b[2], b[3]];
+            if scale < 0. {
+                f32::from_le_bytes(b)
+            } else {
+                f32::from_be_bytes(b)
+            }
+        })
+        .collect();
+    let mut image = Image::new(width, height);
+    for (i, c) in values.chunks(channels).enumerate() {
+        let col = if channels == 3 {
+            Vec3::new(c[0], c[1], c[2])
+        } else {
+            Vec3::new(c[0], c[0], c[0])
+        };
+        // PFM stores the bottom row first
+        image.set(i % width, height - 1 - i / width, col);
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `data` to a file unique to the test and loads it back.
+    fn load_bytes(name: &str, data: &[u8]) -> Result<Image, ImageError> {
+        let path = std::env::temp_dir().join(format!("ray_tracer_{}_{}", std::process::id(), name));
+        fs::write(&path, data).unwrap();
+        let image = Image::load(&path);
+        fs::remove_file(&path).unwrap();
+        image
+    }
+
+    fn gradient(width: usize, height: usize) -> Image {
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                image.set(x, y, Vec3::new(x as f32 / 4., y as f32 / 3., 1.5));
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn round_trips_ppm_binary() {
+        let image = gradient(5, 3);
+        let mut data = Vec::new();
+        image.write_ppm_binary(&mut data).unwrap();
+        let loaded = load_bytes("round_trip.ppm", &data).unwrap();
+        assert_eq!((loaded.width(), loaded.height()), (5, 3));
+        assert_eq!(loaded.to_srgb8(), image.to_srgb8());
+    }
+
+    #[test]
+    fn round_trips_pfm() {
+        let image = gradient(5, 3);
+        let mut data = Vec::new();
+        image.write_pfm(&mut data).unwrap();
+        let loaded = load_bytes("round_trip.pfm", &data).unwrap();
+        assert_eq!((loaded.width(), loaded.height()), (5, 3));
+        for (a, b) in loaded.pixels.iter().zip(&image.pixels) {
+            assert_eq!([a.x, a.y, a.z], [b.x, b.y, b.z]);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_header() {
+        for (name, header) in [
+            ("huge.ppm", "P6\n4294967296 4294967296\n255\n"),
+            ("huge_ascii.ppm", "P3\n4294967296 4294967296\n255\n"),
+            ("huge.pfm", "PF\n4294967296 4294967296\n-1.0\n"),
+        ] {
+            match load_bytes(name, header.as_bytes()) {
+                Err(ImageError::Format(_, message)) => assert_eq!(message, "image too large"),
+                _ => panic!("{} was not rejected", name),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_pixels() {
+        match load_bytes("short.ppm", b"P6\n2 2\n255\n\x01\x02\x03") {
+            Err(ImageError::Format(_, message)) => assert_eq!(message, "truncated pixel data"),
+            _ => panic!("truncated image was not rejected"),
+        }
+    }
+
+    #[test]
+    fn rejects_writing_empty_pfm() {
+        let err = Image::new(0, 3).write_pfm(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
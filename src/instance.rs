@@ -0,0 +1,117 @@
+use crate::aabb::Aabb;
+use crate::hitable::{HitResult, Hitable};
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::transform::Transform;
+use std::sync::Arc;
+
+/// Places a shared object in the world with a transform, so the same mesh or
+/// group can appear several times.
+pub struct Instance {
+    object: Arc<dyn Hitable>,
+    transform: Transform,
+    bbox: Option<Aabb>,
+}
+
+impl Instance {
+    pub fn new(object: Arc<dyn Hitable>, transform: Transform) -> Self {
+        let bbox = object.bounding_box().map(|b| transform.bounding_box(b));
+        Instance {
+            object,
+            transform,
+            bbox,
+        }
+    }
+}
+
+impl Hitable for Instance {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, sampler: &mut Sampler) -> HitResult<'_> {
+        // the direction is not normalized, so `t` means the same in both spaces
+        let inv = self.transform.inverse();
+        let local = Ray::new(inv.point(r.origin()), inv.vector(r.direction()), r.time());
+        let mut rec = self.object.hit(local, t_min, t_max, sampler)?;
+        rec.p = self.transform.point(rec.p);
+        // already faces against `local`, which the transform preserves
+        rec.normal = self.transform.normal(rec.normal).make_unit_vector();
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hitable::Sphere;
+    use crate::material::Lambertian;
+    use crate::vec3::Vec3;
+
+    fn unit_sphere(center: Vec3) -> Arc<dyn Hitable> {
+        Arc::new(Sphere::new(
+            center,
+            1.,
+            Lambertian::new(Vec3::new(0.5, 0.5, 0.5)),
+        ))
+    }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(
+            (a - b).length() < 1e-4,
+            "({}, {}, {}) != ({}, {}, {})",
+            a.x,
+            a.y,
+            a.z,
+            b.x,
+            b.y,
+            b.z
+        );
+    }
+
+    fn hit(object: &dyn Hitable, origin: Vec3, direction: Vec3) -> (f32, Vec3, Vec3) {
+        let r = Ray::new(origin, direction, 0.);
+        let rec = object
+            .hit(r, 0.001, f32::MAX, &mut Sampler::new(0))
+            .unwrap();
+        (rec.t, rec.p, rec.normal)
+    }
+
+    #[test]
+    fn matches_a_scaled_and_moved_sphere() {
+        let instance = Instance::new(
+            unit_sphere(Vec3::new(0., 0., 0.)),
+            Transform::translate(Vec3::new(0., 0., -5.)) * Transform::scale(Vec3::new(2., 2., 2.)),
+        );
+        // same as a sphere of radius 2 at (0, 0, -5)
+        let (t, p, normal) = hit(&instance, Vec3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+        assert!((t - 3.).abs() < 1e-4);
+        assert_close(p, Vec3::new(0., 0., -3.));
+        assert_close(normal, Vec3::new(0., 0., 1.));
+    }
+
+    #[test]
+    fn matches_a_rotated_sphere() {
+        // rotating (2, 0, 0) by 90 degrees around y moves it to (0, 0, -2)
+        let instance = Instance::new(unit_sphere(Vec3::new(2., 0., 0.)), Transform::rotate_y(90.));
+        let (t, p, normal) = hit(&instance, Vec3::new(0., 0., 0.), Vec3::new(0., 0., -2.));
+        assert!((t - 0.5).abs() < 1e-4);
+        assert_close(p, Vec3::new(0., 0., -1.));
+        assert_close(normal, Vec3::new(0., 0., 1.));
+    }
+
+    #[test]
+    fn matches_an_ellipsoid() {
+        // x^2 / 4 + y^2 + z^2 = 1, hit from above at x = 1
+        let instance = Instance::new(
+            unit_sphere(Vec3::new(0., 0., 0.)),
+            Transform::scale(Vec3::new(2., 1., 1.)),
+        );
+        let (t, p, normal) = hit(&instance, Vec3::new(1., 5., 0.), Vec3::new(0., -1., 0.));
+        let y = 0.75f32.sqrt();
+        assert!((t - (5. - y)).abs() < 1e-4);
+        assert_close(p, Vec3::new(1., y, 0.));
+        // gradient of the implicit surface
+        assert_close(normal, Vec3::new(0.5, 2. * y, 0.).make_unit_vector());
+    }
+}
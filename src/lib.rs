@@ -1,5 +1,20 @@
+pub mod aabb;
+pub mod bvh;
 pub mod camera;
 pub mod hitable;
+pub mod image;
+pub mod instance;
 pub mod material;
+pub mod medium;
+pub mod obj;
+pub mod perlin;
+mod png;
 pub mod ray;
+pub mod rect;
+pub mod render;
+pub mod sampler;
+pub mod scene;
+pub mod texture;
+pub mod transform;
+pub mod triangle;
 pub mod vec3;
@@ -1,57 +1,89 @@
 use crate::hitable::HitRecord;
 use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::texture::{SolidColor, Texture};
 use crate::vec3::Vec3;
-use rand::distributions::{Distribution, Uniform};
+use std::sync::Arc;
 
 pub type ScatterResult = Option<(Vec3, Ray)>;
 
-pub trait Material {
-    fn scatter(&self, _: Ray, _: HitRecord) -> ScatterResult {
+pub trait Material: Send + Sync {
+    fn scatter(&self, _: Ray, _: HitRecord, _: &mut Sampler) -> ScatterResult {
         None
     }
+
+    /// Radiance given off by the surface itself.
+    fn emitted(&self, _: HitRecord) -> Vec3 {
+        Vec3::new(0., 0., 0.)
+    }
 }
 
-pub struct Lambertian {
-    albedo: Vec3,
+impl<M: Material + ?Sized> Material for Arc<M> {
+    fn scatter(&self, r_in: Ray, rec: HitRecord, sampler: &mut Sampler) -> ScatterResult {
+        (**self).scatter(r_in, rec, sampler)
+    }
+
+    fn emitted(&self, rec: HitRecord) -> Vec3 {
+        (**self).emitted(rec)
+    }
+}
+
+pub struct Lambertian<T: Texture = SolidColor> {
+    albedo: T,
 }
 
 impl Lambertian {
     pub fn new(albedo: Vec3) -> Self {
+        Self::textured(SolidColor::new(albedo))
+    }
+}
+
+impl<T: Texture> Lambertian<T> {
+    pub fn textured(albedo: T) -> Self {
         Self { albedo }
     }
 }
 
-impl Material for Lambertian {
-    fn scatter(&self, _: Ray, rec: HitRecord) -> Option<(Vec3, Ray)> {
-        let target = rec.p + rec.normal + random_in_unit_sphere();
-        let scattered = Ray::new(rec.p, target - rec.p);
-        Some((self.albedo, scattered))
+impl<T: Texture> Material for Lambertian<T> {
+    fn scatter(&self, r_in: Ray, rec: HitRecord, sampler: &mut Sampler) -> Option<(Vec3, Ray)> {
+        let target = rec.p + rec.normal + random_in_unit_sphere(sampler);
+        let scattered = Ray::new(rec.p, target - rec.p, r_in.time());
+        Some((self.albedo.value(rec.u, rec.v, rec.p), scattered))
     }
 }
 
-fn random_in_unit_sphere() -> Vec3 {
-    let between = Uniform::new(0., 1.);
-    let mut rng = rand::thread_rng();
+pub fn random_in_unit_sphere(sampler: &mut Sampler) -> Vec3 {
     let mut p: Vec3;
     while {
-        p = Vec3::new(
-            between.sample(&mut rng),
-            between.sample(&mut rng),
-            between.sample(&mut rng),
-        ) * 2.0
+        p = Vec3::new(sampler.next_f32(), sampler.next_f32(), sampler.next_f32()) * 2.0
             - Vec3::new(1., 1., 1.);
         p.squared_length() >= 1.
     } {}
-    return p;
+    p
+}
+
+pub fn random_in_unit_disk(sampler: &mut Sampler) -> Vec3 {
+    let mut p: Vec3;
+    while {
+        p = Vec3::new(sampler.next_f32(), sampler.next_f32(), 0.) * 2.0 - Vec3::new(1., 1., 0.);
+        p.squared_length() >= 1.
+    } {}
+    p
 }
 
-pub struct Metal {
-    albedo: Vec3,
+pub struct Metal<T: Texture = SolidColor> {
+    albedo: T,
     fuzz: f32,
 }
 
 impl Metal {
     pub fn new(albedo: Vec3, fuzz: f32) -> Self {
+        Self::textured(SolidColor::new(albedo), fuzz)
+    }
+}
+
+impl<T: Texture> Metal<T> {
+    pub fn textured(albedo: T, fuzz: f32) -> Self {
         Self {
             albedo,
             fuzz: if fuzz < 1. { fuzz } else { 1.0 },
@@ -59,18 +91,148 @@ impl Metal {
     }
 }
 
-impl Material for Metal {
-    fn scatter(&self, r_in: Ray, rec: HitRecord) -> Option<(Vec3, Ray)> {
+impl<T: Texture> Material for Metal<T> {
+    fn scatter(&self, r_in: Ray, rec: HitRecord, sampler: &mut Sampler) -> Option<(Vec3, Ray)> {
         let reflected = reflect(r_in.direction(), rec.normal);
-        let scattered = Ray::new(rec.p, reflected + random_in_unit_sphere() * self.fuzz);
+        let scattered = Ray::new(
+            rec.p,
+            reflected + random_in_unit_sphere(sampler) * self.fuzz,
+            r_in.time(),
+        );
         if scattered.direction().dot(rec.normal) > 0. {
-            Some((self.albedo, scattered))
+            Some((self.albedo.value(rec.u, rec.v, rec.p), scattered))
         } else {
             None
         }
     }
 }
 
+/// Mirrors the direction of `v` about `n`, returning a unit vector whatever
+/// the length of `v`.
 fn reflect(v: Vec3, n: Vec3) -> Vec3 {
-    v.make_unit_vector() - n * (v.dot(n) * 2.)
+    let v = v.make_unit_vector();
+    v - n * (v.dot(n) * 2.)
+}
+
+fn refract(v: Vec3, n: Vec3, ni_over_nt: f32) -> Option<Vec3> {
+    let uv = v.make_unit_vector();
+    let dt = uv.dot(n);
+    let discriminant = 1.0 - ni_over_nt * ni_over_nt * (1. - dt * dt);
+    if discriminant > 0. {
+        Some((uv - n * dt) * ni_over_nt - n * discriminant.sqrt())
+    } else {
+        None
+    }
+}
+
+fn schlick(cosine: f32, ref_idx: f32) -> f32 {
+    let r0 = (1. - ref_idx) / (1. + ref_idx);
+    let r0 = r0 * r0;
+    r0 + (1. - r0) * (1. - cosine).powi(5)
+}
+
+pub struct Dielectric {
+    ref_idx: f32,
+}
+
+impl Dielectric {
+    pub fn new(ref_idx: f32) -> Self {
+        Self { ref_idx }
+    }
+}
+
+impl Material for Dielectric {
+    fn scatter(&self, r_in: Ray, rec: HitRecord, sampler: &mut Sampler) -> Option<(Vec3, Ray)> {
+        let attenuation = Vec3::new(1., 1., 1.);
+        let ni_over_nt = if rec.front_face {
+            1.0 / self.ref_idx
+        } else {
+            self.ref_idx
+        };
+        let unit_direction = r_in.direction().make_unit_vector();
+        let cosine = (-unit_direction).dot(rec.normal).min(1.0);
+        let direction = match refract(unit_direction, rec.normal, ni_over_nt) {
+            Some(refracted) if sampler.next_f32() >= schlick(cosine, self.ref_idx) => refracted,
+            // total internal reflection or Fresnel reflection
+            _ => reflect(unit_direction, rec.normal),
+        };
+        Some((attenuation, Ray::new(rec.p, direction, r_in.time())))
+    }
+}
+
+pub struct DiffuseLight<T: Texture = SolidColor> {
+    emit: T,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Vec3) -> Self {
+        Self::textured(SolidColor::new(emit))
+    }
+}
+
+impl<T: Texture> DiffuseLight<T> {
+    pub fn textured(emit: T) -> Self {
+        Self { emit }
+    }
+}
+
+impl<T: Texture> Material for DiffuseLight<T> {
+    fn emitted(&self, rec: HitRecord) -> Vec3 {
+        self.emit.value(rec.u, rec.v, rec.p)
+    }
+}
+
+/// Scatters in a uniformly random direction. Used as the phase function of
+/// participating media.
+pub struct Isotropic<T: Texture = SolidColor> {
+    albedo: T,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Vec3) -> Self {
+        Self::textured(SolidColor::new(albedo))
+    }
+}
+
+impl<T: Texture> Isotropic<T> {
+    pub fn textured(albedo: T) -> Self {
+        Self { albedo }
+    }
+}
+
+impl<T: Texture> Material for Isotropic<T> {
+    fn scatter(&self, r_in: Ray, rec: HitRecord, sampler: &mut Sampler) -> Option<(Vec3, Ray)> {
+        let scattered = Ray::new(rec.p, random_in_unit_sphere(sampler), r_in.time());
+        Some((self.albedo.value(rec.u, rec.v, rec.p), scattered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scatter_direction(mat: &dyn Material, direction: Vec3) -> Vec3 {
+        let r = Ray::new(Vec3::new(0., 2., 0.), direction, 0.);
+        let t = 1. / direction.length();
+        let rec = HitRecord::new(r, t, Vec3::new(0., 1., 0.), mat);
+        let (_, scattered) = mat.scatter(r, rec, &mut Sampler::new(1)).unwrap();
+        scattered.direction()
+    }
+
+    #[test]
+    fn metal_does_not_depend_on_ray_length() {
+        for fuzz in [0., 0.3] {
+            let metal = Metal::new(Vec3::new(0.8, 0.8, 0.8), fuzz);
+            let unit = Vec3::new(1., -1., 0.).make_unit_vector();
+            let short = scatter_direction(&metal, unit);
+            let long = scatter_direction(&metal, unit * 10.);
+            assert!((short - long).length() < 1e-5, "fuzz {}", fuzz);
+        }
+        let mirror = scatter_direction(
+            &Metal::new(Vec3::new(1., 1., 1.), 0.),
+            Vec3::new(3., -3., 0.),
+        );
+        let expected = Vec3::new(1., 1., 0.).make_unit_vector();
+        assert!((mirror - expected).length() < 1e-5);
+    }
 }
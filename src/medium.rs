@@ -0,0 +1,116 @@
+use crate::aabb::Aabb;
+use crate::hitable::{HitRecord, HitResult, Hitable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// Volume of constant density filling a closed `boundary`, like smoke or
+/// fog. Rays passing through are scattered at a random distance, more often
+/// the denser the medium is.
+pub struct ConstantMedium<T: Material> {
+    boundary: Arc<dyn Hitable>,
+    neg_inv_density: f32,
+    phase_function: T,
+}
+
+impl<T: Material> ConstantMedium<T> {
+    /// `phase_function` is usually an `Isotropic` material.
+    pub fn new(boundary: Arc<dyn Hitable>, density: f32, phase_function: T) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1. / density,
+            phase_function,
+        }
+    }
+}
+
+impl<T: Material> Hitable for ConstantMedium<T> {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, sampler: &mut Sampler) -> HitResult<'_> {
+        // where the ray enters and leaves the boundary, even outside
+        // [t_min, t_max], so rays starting inside the volume work too
+        let enter = self.boundary.hit(r, f32::MIN, f32::MAX, sampler)?;
+        let exit = self.boundary.hit(r, enter.t + 0.0001, f32::MAX, sampler)?;
+        let t_enter = enter.t.max(t_min).max(0.);
+        let t_exit = exit.t.min(t_max);
+        if t_enter >= t_exit {
+            return None;
+        }
+        let ray_length = r.direction().length();
+        let distance_inside = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * sampler.next_f32().ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+        // the normal and front_face are meaningless inside a volume
+        Some(HitRecord::new(
+            r,
+            t_enter + hit_distance / ray_length,
+            Vec3::new(1., 0., 0.),
+            &self.phase_function,
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bvh::BvhNode;
+    use crate::hitable::Sphere;
+    use crate::material::Isotropic;
+    use crate::rect::BoxShape;
+
+    fn unit_box_medium(density: f32) -> Arc<dyn Hitable> {
+        let white = Isotropic::new(Vec3::new(1., 1., 1.));
+        let boundary = Arc::new(BoxShape::new(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(1., 1., 1.),
+            Isotropic::new(Vec3::new(1., 1., 1.)),
+        ));
+        Arc::new(ConstantMedium::new(boundary, density, white))
+    }
+
+    /// Fraction of rays crossing the unit box along x that scatter.
+    fn hit_rate(world: &dyn Hitable) -> f32 {
+        let mut sampler = Sampler::new(7);
+        let r = Ray::new(Vec3::new(-1., 0.5, 0.5), Vec3::new(1., 0., 0.), 0.);
+        let n = 20_000;
+        let hits = (0..n)
+            .filter(|_| world.hit(r, 0.001, f32::MAX, &mut sampler).is_some())
+            .count();
+        hits as f32 / n as f32
+    }
+
+    #[test]
+    fn hit_rate_follows_density() {
+        let expected = 1. - (-0.5f32).exp();
+        let rate = hit_rate(&*unit_box_medium(0.5));
+        assert!((rate - expected).abs() < 0.02, "rate {}", rate);
+    }
+
+    #[test]
+    fn hit_rate_is_the_same_inside_a_bvh() {
+        let direct = hit_rate(&*unit_box_medium(0.5));
+
+        let single = BvhNode::new(vec![unit_box_medium(0.5)]);
+        let rate = hit_rate(&single);
+        assert!((rate - direct).abs() < 0.02, "single leaf rate {}", rate);
+
+        // the spheres are above the medium, leaving it alone in a leaf
+        let sphere = |y| {
+            Arc::new(Sphere::new(
+                Vec3::new(0.5, y, 0.5),
+                0.5,
+                Isotropic::new(Vec3::new(1., 1., 1.)),
+            )) as Arc<dyn Hitable>
+        };
+        let three = BvhNode::new(vec![sphere(20.), unit_box_medium(0.5), sphere(10.)]);
+        let rate = hit_rate(&three);
+        assert!((rate - direct).abs() < 0.02, "odd leaf rate {}", rate);
+    }
+}
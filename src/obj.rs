@@ -0,0 +1,372 @@
+//! Wavefront OBJ loader. Supports `v`, `vn`, `vt` and `f` statements, with
+//! polygons triangulated as fans and every `o`/`g` statement starting a new
+//! mesh. Other statements such as `usemtl` or `s` are ignored.
+
+use crate::hitable::HitableList;
+use crate::material::Material;
+use crate::triangle::{Face, Mesh, MeshData};
+use crate::vec3::Vec3;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io(io::Error),
+    /// A malformed statement at the given 1-based line.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "{}", err),
+            ObjError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Io(err) => Some(err),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+/// Faces collected under one `o` or `g` statement.
+#[derive(Clone, Debug)]
+pub struct ObjGroup {
+    pub name: String,
+    pub data: MeshData,
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<ObjGroup>, ObjError> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Turns every group into a `Mesh` sharing `mat`.
+pub fn into_hitable<T: Material + Clone + 'static>(groups: Vec<ObjGroup>, mat: T) -> HitableList {
+    let mut list = HitableList::new();
+    for group in groups {
+        list.add(Arc::new(Mesh::new(Arc::new(group.data), mat.clone())));
+    }
+    list
+}
+
+pub fn parse(src: &str) -> Result<Vec<ObjGroup>, ObjError> {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut groups = Vec::new();
+    let mut current = GroupBuilder::new(String::new());
+
+    for (i, line) in src.lines().enumerate() {
+        let line_no = i + 1;
+        let err = |message: String| ObjError::Parse {
+            line: line_no,
+            message,
+        };
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+        match keyword {
+            "v" => positions.push(parse_vec3(&args, 3, 4).map_err(err)?),
+            "vn" => normals.push(parse_vec3(&args, 3, 3).map_err(err)?),
+            "vt" => {
+                let uv = parse_vec3(&args, 1, 3).map_err(err)?;
+                uvs.push((uv.x, uv.y));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(err(format!(
+                        "face needs at least 3 vertices, got {}",
+                        args.len()
+                    )));
+                }
+                let vertices = args
+                    .iter()
+                    .map(|arg| parse_face_vertex(arg, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(err)?;
+                for k in 1..vertices.len() - 1 {
+                    current.add_triangle(
+                        [vertices[0], vertices[k], vertices[k + 1]],
+                        &positions,
+                        &uvs,
+                        &normals,
+                    );
+                }
+            }
+            "o" | "g" => {
+                let name = args.join(" ");
+                let finished = std::mem::replace(&mut current, GroupBuilder::new(name));
+                groups.extend(finished.build());
+            }
+            _ => {}
+        }
+    }
+    groups.extend(current.build());
+    Ok(groups)
+}
+
+fn parse_vec3(args: &[&str], min: usize, max: usize) -> Result<Vec3, String> {
+    if args.len() < min || args.len() > max {
+        return Err(format!(
+            "expected {} to {} numbers, got {}",
+            min,
+            max,
+            args.len()
+        ));
+    }
+    let mut v = [0.; 3];
+    for (c, arg) in v.iter_mut().zip(args) {
+        *c = arg
+            .parse()
+            .map_err(|_| format!("invalid number `{}`", arg))?;
+    }
+    Ok(Vec3::new(v[0], v[1], v[2]))
+}
+
+/// Global (position, uv, normal) indices of one `v/vt/vn` face vertex.
+type FaceVertex = (usize, Option<usize>, Option<usize>);
+
+fn parse_face_vertex(
+    arg: &str,
+    n_positions: usize,
+    n_uvs: usize,
+    n_normals: usize,
+) -> Result<FaceVertex, String> {
+    let mut parts = arg.split('/');
+    let position = match parts.next() {
+        Some(index) => resolve_index(index, n_positions, "vertex")?,
+        None => return Err(format!("invalid face vertex `{}`", arg)),
+    };
+    let uv = match parts.next() {
+        Some("") | None => None,
+        Some(index) => Some(resolve_index(index, n_uvs, "texture coordinate")?),
+    };
+    let normal = match parts.next() {
+        Some("") | None => None,
+        Some(index) => Some(resolve_index(index, n_normals, "normal")?),
+    };
+    if parts.next().is_some() {
+        return Err(format!("invalid face vertex `{}`", arg));
+    }
+    Ok((position, uv, normal))
+}
+
+/// Converts a 1-based, possibly negative (relative to the end) index into a
+/// 0-based one.
+fn resolve_index(index: &str, len: usize, what: &str) -> Result<usize, String> {
+    let i: i64 = index
+        .parse()
+        .map_err(|_| format!("invalid {} index `{}`", what, index))?;
+    let resolved = if i > 0 { i - 1 } else { len as i64 + i };
+    if i == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(format!(
+            "{} index {} is out of range ({} defined)",
+            what, index, len
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+/// Copies the vertices used by a group into its own buffers.
+struct GroupBuilder {
+    name: String,
+    data: MeshData,
+    positions: HashMap<usize, usize>,
+    uvs: HashMap<usize, usize>,
+    normals: HashMap<usize, usize>,
+}
+
+impl GroupBuilder {
+    fn new(name: String) -> Self {
+        GroupBuilder {
+            name,
+            data: MeshData::default(),
+            positions: HashMap::new(),
+            uvs: HashMap::new(),
+            normals: HashMap::new(),
+        }
+    }
+
+    fn add_triangle(
+        &mut self,
+        vertices: [FaceVertex; 3],
+        positions: &[Vec3],
+        uvs: &[(f32, f32)],
+        normals: &[Vec3],
+    ) {
+        let data = &mut self.data;
+        let mut face = Face {
+            positions: [0; 3],
+            normals: Some([0; 3]),
+            uvs: Some([0; 3]),
+        };
+        for (k, &(p, uv, n)) in vertices.iter().enumerate() {
+            face.positions[k] = remap(&mut self.positions, &mut data.positions, positions, p);
+            face.uvs = match (face.uvs, uv) {
+                (Some(mut f), Some(uv)) => {
+                    f[k] = remap(&mut self.uvs, &mut data.uvs, uvs, uv);
+                    Some(f)
+                }
+                _ => None,
+            };
+            face.normals = match (face.normals, n) {
+                (Some(mut f), Some(n)) => {
+                    f[k] = remap(&mut self.normals, &mut data.normals, normals, n);
+                    Some(f)
+                }
+                _ => None,
+            };
+        }
+        data.faces.push(face);
+    }
+
+    fn build(self) -> Option<ObjGroup> {
+        if self.data.faces.is_empty() {
+            None
+        } else {
+            Some(ObjGroup {
+                name: self.name,
+                data: self.data,
+            })
+        }
+    }
+}
+
+fn remap<T: Copy>(
+    map: &mut HashMap<usize, usize>,
+    local: &mut Vec<T>,
+    global: &[T],
+    i: usize,
+) -> usize {
+    *map.entry(i).or_insert_with(|| {
+        local.push(global[i]);
+        local.len() - 1
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(src: &str) -> String {
+        match parse(src) {
+            Err(err) => err.to_string(),
+            Ok(_) => panic!("OBJ parsed"),
+        }
+    }
+
+    #[test]
+    fn fans_polygons_into_triangles() {
+        let groups = parse(
+            "v 0 0 0\n\
+             v 1 0 0\n\
+             v 1 1 0\n\
+             v 0 1 0\n\
+             f 1 2 3 4\n",
+        )
+        .unwrap();
+        assert_eq!(groups.len(), 1);
+        let faces = &groups[0].data.faces;
+        assert_eq!(faces.len(), 2);
+        assert_eq!(faces[0].positions, [0, 1, 2]);
+        assert_eq!(faces[1].positions, [0, 2, 3]);
+    }
+
+    #[test]
+    fn resolves_negative_indices() {
+        let groups = parse(
+            "v 5 5 5\n\
+             v 0 0 0\n\
+             v 1 0 0\n\
+             v 0 1 0\n\
+             f -3 -2 -1\n",
+        )
+        .unwrap();
+        let data = &groups[0].data;
+        // only the vertices used by the group are copied
+        assert_eq!(data.positions.len(), 3);
+        let face = data.faces[0];
+        let x: Vec<f32> = face
+            .positions
+            .iter()
+            .map(|&i| data.positions[i].x)
+            .collect();
+        assert_eq!(x, [0., 1., 0.]);
+    }
+
+    #[test]
+    fn reads_normals_without_uvs() {
+        let groups = parse(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             vn 0 0 1\nvn 0 0 1\nvn 0 0 1\n\
+             f 1//1 2//2 3//3\n",
+        )
+        .unwrap();
+        let data = &groups[0].data;
+        let face = data.faces[0];
+        assert!(face.uvs.is_none());
+        let normals = face.normals.unwrap();
+        assert!(normals.iter().all(|&i| data.normals[i].z == 1.));
+    }
+
+    #[test]
+    fn splits_groups() {
+        let groups = parse(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\n\
+             o empty\n\
+             o first\n\
+             f 1 2 3\n\
+             g second part\n\
+             f 2 4 3\n\
+             f 1 2 4\n",
+        )
+        .unwrap();
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["first", "second part"]);
+        assert_eq!(groups[0].data.faces.len(), 1);
+        assert_eq!(groups[0].data.positions.len(), 3);
+        assert_eq!(groups[1].data.faces.len(), 2);
+        assert_eq!(groups[1].data.positions.len(), 4);
+        // indices are local to the group
+        assert_eq!(groups[1].data.faces[0].positions, [0, 1, 2]);
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 0 0\n\nf 1 2 3\n"),
+            "line 4: vertex index 3 is out of range (2 defined)"
+        );
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -4 1 2\n"),
+            "line 4: vertex index -4 is out of range (3 defined)"
+        );
+        assert_eq!(
+            parse_error("# comment\nv 0 x 0\n"),
+            "line 2: invalid number `x`"
+        );
+    }
+}
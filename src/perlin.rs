@@ -0,0 +1,104 @@
+use crate::sampler::Sampler;
+use crate::vec3::Vec3;
+
+const POINT_COUNT: usize = 256;
+
+/// Perlin gradient noise. The lattice only depends on the seed.
+pub struct Perlin {
+    ranvec: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut sampler = Sampler::new(seed);
+        let ranvec = (0..POINT_COUNT)
+            .map(|_| {
+                (Vec3::new(sampler.next_f32(), sampler.next_f32(), sampler.next_f32()) * 2.
+                    - Vec3::new(1., 1., 1.))
+                .make_unit_vector()
+            })
+            .collect();
+        let perm_x = generate_perm(&mut sampler);
+        let perm_y = generate_perm(&mut sampler);
+        let perm_z = generate_perm(&mut sampler);
+        Perlin {
+            ranvec,
+            perm_x,
+            perm_y,
+            perm_z,
+        }
+    }
+
+    /// Smooth noise in [-1, 1].
+    pub fn noise(&self, p: Vec3) -> f32 {
+        let (fx, fy, fz) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (u, v, w) = (p.x - fx, p.y - fy, p.z - fz);
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+        let mut c = [[[Vec3::new(0., 0., 0.); 2]; 2]; 2];
+        for (di, plane) in c.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    let index = self.perm_x[lattice(i, di)]
+                        ^ self.perm_y[lattice(j, dj)]
+                        ^ self.perm_z[lattice(k, dk)];
+                    *corner = self.ranvec[index];
+                }
+            }
+        }
+        perlin_interp(&c, u, v, w)
+    }
+
+    /// Sum of `depth` octaves of noise, each at twice the frequency and half
+    /// the weight of the previous one.
+    pub fn turb(&self, p: Vec3, depth: usize) -> f32 {
+        let mut accum = 0.;
+        let mut temp_p = p;
+        let mut weight = 1.;
+        for _ in 0..depth {
+            accum += weight * self.noise(temp_p);
+            weight *= 0.5;
+            temp_p *= 2.;
+        }
+        accum.abs()
+    }
+}
+
+fn lattice(i: i64, offset: usize) -> usize {
+    (i + offset as i64).rem_euclid(POINT_COUNT as i64) as usize
+}
+
+fn generate_perm(sampler: &mut Sampler) -> Vec<usize> {
+    let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+    for i in (1..POINT_COUNT).rev() {
+        let target = ((sampler.next_f32() * (i + 1) as f32) as usize).min(i);
+        p.swap(i, target);
+    }
+    p
+}
+
+/// Trilinear interpolation of the gradients at the cell corners, with Hermite
+/// smoothing of the weights.
+fn perlin_interp(c: &[[[Vec3; 2]; 2]; 2], u: f32, v: f32, w: f32) -> f32 {
+    let (uu, vv, ww) = (
+        u * u * (3. - 2. * u),
+        v * v * (3. - 2. * v),
+        w * w * (3. - 2. * w),
+    );
+    let mut accum = 0.;
+    for (i, plane) in c.iter().enumerate() {
+        for (j, row) in plane.iter().enumerate() {
+            for (k, gradient) in row.iter().enumerate() {
+                let (fi, fj, fk) = (i as f32, j as f32, k as f32);
+                let weight = Vec3::new(u - fi, v - fj, w - fk);
+                accum += (fi * uu + (1. - fi) * (1. - uu))
+                    * (fj * vv + (1. - fj) * (1. - vv))
+                    * (fk * ww + (1. - fk) * (1. - ww))
+                    * gradient.dot(weight);
+            }
+        }
+    }
+    accum
+}
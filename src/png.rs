@@ -0,0 +1,640 @@
+//! Minimal PNG codec. The encoder stores image data in uncompressed deflate
+//! blocks and the decoder comes with its own inflate, so no compression
+//! library is needed.
+
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+/// Largest payload of a stored deflate block.
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+/// Writes an 8-bit RGB PNG. `rgb` holds `width * height` interleaved pixels,
+/// starting from the top row.
+pub fn write_rgb8<W: Write>(w: &mut W, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    assert_eq!(rgb.len(), width * height * 3);
+    if width == 0 || height == 0 || width > 0x7fff_ffff || height > 0x7fff_ffff {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot encode a {}x{} PNG", width, height),
+        ));
+    }
+    w.write_all(&SIGNATURE)?;
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
+    // bit depth 8, color type RGB, deflate, adaptive filtering, no interlace
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(w, b"IHDR", &ihdr)?;
+
+    // every scanline starts with its filter type, which is always "None" here
+    let mut raw = Vec::with_capacity(height * (width * 3 + 1));
+    for row in rgb.chunks(width * 3) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    write_chunk(w, b"IDAT", &zlib_stored(&raw))?;
+    write_chunk(w, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    w.write_all(&(data.len() as u32).to_be_bytes())?;
+    w.write_all(kind)?;
+    w.write_all(data)?;
+    let crc = crc32_update(crc32_update(0xffff_ffff, kind), data) ^ 0xffff_ffff;
+    w.write_all(&crc.to_be_bytes())
+}
+
+/// Wraps `data` in a zlib stream made of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len() / MAX_STORED_BLOCK + 1;
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    // deflate with a 32K window, no preset dictionary
+    out.extend_from_slice(&[0x78, 0x01]);
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Decodes a non-interlaced PNG of any color type into `width * height`
+/// interleaved RGB values in [0, 1], still sRGB encoded. Alpha is dropped.
+pub fn read_rgb(data: &[u8]) -> Result<(usize, usize, Vec<f32>), String> {
+    if !data.starts_with(&SIGNATURE) {
+        return Err("missing PNG signature".to_string());
+    }
+    let mut header = None;
+    let mut palette = Vec::new();
+    let mut idat = Vec::new();
+    let mut rest = &data[SIGNATURE.len()..];
+    loop {
+        if rest.len() < 12 {
+            return Err("truncated chunk".to_string());
+        }
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        if rest.len() < 12 + len {
+            return Err("truncated chunk".to_string());
+        }
+        let kind = &rest[4..8];
+        let body = &rest[8..8 + len];
+        let crc =
+            u32::from_be_bytes([rest[8 + len], rest[9 + len], rest[10 + len], rest[11 + len]]);
+        if crc32_update(crc32_update(0xffff_ffff, kind), body) ^ 0xffff_ffff != crc {
+            return Err(format!(
+                "bad checksum in {} chunk",
+                String::from_utf8_lossy(kind)
+            ));
+        }
+        match kind {
+            b"IHDR" => header = Some(Header::parse(body)?),
+            b"PLTE" => palette = body.to_vec(),
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = &rest[12 + len..];
+    }
+    let header = header.ok_or("missing IHDR chunk")?;
+    let raw = zlib_inflate(&idat, header.data_size()?)?;
+    let rows = unfilter(&header, &raw)?;
+    header.to_rgb(&rows, &palette)
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+    /// Bytes per scanline, without the filter type byte.
+    row_bytes: usize,
+}
+
+impl Header {
+    fn parse(body: &[u8]) -> Result<Self, String> {
+        if body.len() != 13 {
+            return Err("malformed IHDR chunk".to_string());
+        }
+        let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+        let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize;
+        let (bit_depth, color_type) = (body[8], body[9]);
+        if width == 0 || height == 0 {
+            return Err("image has no pixels".to_string());
+        }
+        if width > 0x7fff_ffff || height > 0x7fff_ffff {
+            return Err("image too large".to_string());
+        }
+        let depth_ok = match color_type {
+            0 => [1, 2, 4, 8, 16].contains(&bit_depth),
+            3 => [1, 2, 4, 8].contains(&bit_depth),
+            2 | 4 | 6 => [8, 16].contains(&bit_depth),
+            _ => return Err(format!("invalid color type {}", color_type)),
+        };
+        if !depth_ok {
+            return Err(format!(
+                "invalid bit depth {} for color type {}",
+                bit_depth, color_type
+            ));
+        }
+        if body[10] != 0 || body[11] != 0 {
+            return Err("unknown compression or filter method".to_string());
+        }
+        if body[12] != 0 {
+            return Err("interlaced PNGs are not supported".to_string());
+        }
+        let mut header = Header {
+            width,
+            height,
+            bit_depth,
+            color_type,
+            row_bytes: 0,
+        };
+        header.row_bytes = width
+            .checked_mul(header.channels() * usize::from(bit_depth))
+            .ok_or("image too large")?
+            .div_ceil(8);
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.color_type {
+            2 => 3,
+            4 => 2,
+            6 => 4,
+            _ => 1,
+        }
+    }
+
+    /// Size of the decompressed image data, filter type bytes included.
+    fn data_size(&self) -> Result<usize, String> {
+        (self.row_bytes + 1)
+            .checked_mul(self.height)
+            .ok_or_else(|| "image too large".to_string())
+    }
+
+    /// Distance in bytes to the corresponding byte of the previous pixel.
+    fn filter_distance(&self) -> usize {
+        (self.channels() * self.bit_depth as usize / 8).max(1)
+    }
+
+    fn sample(&self, row: &[u8], i: usize) -> u16 {
+        match self.bit_depth {
+            16 => u16::from_be_bytes([row[2 * i], row[2 * i + 1]]),
+            8 => u16::from(row[i]),
+            bits => {
+                let bits = bits as usize;
+                let shift = 8 - bits - (i * bits) % 8;
+                u16::from((row[i * bits / 8] >> shift) & ((1 << bits) - 1) as u8)
+            }
+        }
+    }
+
+    fn to_rgb(&self, rows: &[u8], palette: &[u8]) -> Result<(usize, usize, Vec<f32>), String> {
+        let max = ((1u32 << self.bit_depth) - 1) as f32;
+        let channels = self.channels();
+        let mut rgb = Vec::with_capacity(self.width * self.height * 3);
+        for row in rows.chunks(self.row_bytes) {
+            for x in 0..self.width {
+                let sample = |c: usize| self.sample(row, x * channels + c);
+                match self.color_type {
+                    3 => {
+                        let i = sample(0) as usize * 3;
+                        let entry = palette.get(i..i + 3).ok_or("palette index out of range")?;
+                        rgb.extend(entry.iter().map(|&c| f32::from(c) / 255.));
+                    }
+                    0 | 4 => {
+                        let gray = f32::from(sample(0)) / max;
+                        rgb.extend_from_slice(&[gray, gray, gray]);
+                    }
+                    _ => rgb.extend((0..3).map(|c| f32::from(sample(c)) / max)),
+                }
+            }
+        }
+        Ok((self.width, self.height, rgb))
+    }
+}
+
+/// Reverses the per-scanline filters, returning the rows without filter bytes.
+fn unfilter(header: &Header, raw: &[u8]) -> Result<Vec<u8>, String> {
+    let row_bytes = header.row_bytes;
+    if raw.len() != header.data_size()? {
+        return Err("image data has the wrong size".to_string());
+    }
+    let bpp = header.filter_distance();
+    let mut rows = vec![0u8; header.height * row_bytes];
+    for y in 0..header.height {
+        let line = &raw[y * (row_bytes + 1)..(y + 1) * (row_bytes + 1)];
+        let (done, current) = rows.split_at_mut(y * row_bytes);
+        let prior = if y == 0 {
+            None
+        } else {
+            Some(&done[(y - 1) * row_bytes..])
+        };
+        let current = &mut current[..row_bytes];
+        for i in 0..row_bytes {
+            let a = if i >= bpp { current[i - bpp] } else { 0 };
+            let b = prior.map_or(0, |p| p[i]);
+            let c = match prior {
+                Some(p) if i >= bpp => p[i - bpp],
+                _ => 0,
+            };
+            let predictor = match line[0] {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((u16::from(a) + u16::from(b)) / 2) as u8,
+                4 => paeth(a, b, c),
+                f => return Err(format!("invalid filter type {}", f)),
+            };
+            current[i] = line[i + 1].wrapping_add(predictor);
+        }
+    }
+    Ok(rows)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = i16::from(a) + i16::from(b) - i16::from(c);
+    let (pa, pb, pc) = (
+        (p - i16::from(a)).abs(),
+        (p - i16::from(b)).abs(),
+        (p - i16::from(c)).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Fails as soon as the output grows past `limit` bytes, so that corrupt data
+/// cannot exhaust memory.
+fn zlib_inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    if data.len() < 6 {
+        return Err("truncated zlib stream".to_string());
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    if cmf & 0x0f != 8 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 || flg & 0x20 != 0 {
+        return Err("invalid zlib header".to_string());
+    }
+    let out = inflate(&data[2..], limit)?;
+    let expected = &data[data.len() - 4..];
+    if adler32(&out).to_be_bytes() != expected {
+        return Err("bad zlib checksum".to_string());
+    }
+    Ok(out)
+}
+
+/// Least-significant-bit first reader over a deflate stream.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, n: u32) -> Result<u32, String> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = *self.data.get(self.pos).ok_or("truncated deflate stream")?;
+            value |= u32::from((byte >> self.bit) & 1) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit != 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
+
+/// Canonical Huffman code given by the number of codes of each length and the
+/// symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|&s| lengths[s as usize] != 0)
+            .collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]);
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, br: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= br.bits(1)? as i32;
+            let count = i32::from(self.counts[len]);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code".to_string())
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let mut br = BitReader {
+        data,
+        pos: 0,
+        bit: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = br.bits(1)? == 1;
+        match br.bits(2)? {
+            0 => {
+                br.align_to_byte();
+                let header = data
+                    .get(br.pos..br.pos + 4)
+                    .ok_or("truncated stored block")?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return Err("corrupt stored block length".to_string());
+                }
+                if out.len() + len as usize > limit {
+                    return Err(TOO_MUCH_DATA.to_string());
+                }
+                let start = br.pos + 4;
+                let block = data
+                    .get(start..start + len as usize)
+                    .ok_or("truncated stored block")?;
+                out.extend_from_slice(block);
+                br.pos = start + len as usize;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                for (s, len) in lengths.iter_mut().enumerate() {
+                    *len = match s {
+                        0..=143 => 8,
+                        144..=255 => 9,
+                        256..=279 => 7,
+                        _ => 8,
+                    };
+                }
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                inflate_block(&mut br, &mut out, limit, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_codes(&mut br)?;
+                inflate_block(&mut br, &mut out, limit, &literals, &distances)?;
+            }
+            _ => return Err("invalid deflate block type".to_string()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+const TOO_MUCH_DATA: &str = "image data is larger than expected";
+
+fn read_dynamic_codes(br: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let hlit = br.bits(5)? as usize + 257;
+    let hdist = br.bits(5)? as usize + 1;
+    let hclen = br.bits(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..hclen] {
+        code_lengths[i] = br.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+    let mut lengths = Vec::with_capacity(hlit + hdist);
+    while lengths.len() < hlit + hdist {
+        let (value, repeat) = match code_lengths.decode(br)? {
+            len @ 0..=15 => (len as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or("repeat without a previous length")?;
+                (previous, 3 + br.bits(2)?)
+            }
+            17 => (0, 3 + br.bits(3)?),
+            _ => (0, 11 + br.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() != hlit + hdist {
+        return Err("too many code lengths".to_string());
+    }
+    Ok((
+        Huffman::new(&lengths[..hlit]),
+        Huffman::new(&lengths[hlit..]),
+    ))
+}
+
+fn inflate_block(
+    br: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = literals.decode(br)? as usize;
+        match symbol {
+            0..=255 if out.len() >= limit => return Err(TOO_MUCH_DATA.to_string()),
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let i = symbol - 257;
+                if i >= LENGTH_BASE.len() {
+                    return Err("invalid length code".to_string());
+                }
+                let len = LENGTH_BASE[i] as usize + br.bits(u32::from(LENGTH_EXTRA[i]))? as usize;
+                let d = distances.decode(br)? as usize;
+                if d >= DIST_BASE.len() {
+                    return Err("invalid distance code".to_string());
+                }
+                let dist = DIST_BASE[d] as usize + br.bits(u32::from(DIST_EXTRA[d]))? as usize;
+                if dist > out.len() {
+                    return Err("distance too far back".to_string());
+                }
+                if out.len() + len > limit {
+                    return Err(TOO_MUCH_DATA.to_string());
+                }
+                let start = out.len() - dist;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PNG made of the given chunks, each as (kind, body).
+    fn png(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut data = SIGNATURE.to_vec();
+        for (kind, body) in chunks {
+            write_chunk(&mut data, kind, body).unwrap();
+        }
+        data
+    }
+
+    fn ihdr(width: u32, height: u32, bit_depth: u8, color_type: u8) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&width.to_be_bytes());
+        body.extend_from_slice(&height.to_be_bytes());
+        body.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        body
+    }
+
+    #[test]
+    fn round_trips_rgb8() {
+        let (width, height) = (7, 3);
+        let rgb: Vec<u8> = (0..width * height * 3).map(|i| (i * 11) as u8).collect();
+        let mut data = Vec::new();
+        write_rgb8(&mut data, width, height, &rgb).unwrap();
+        let (w, h, decoded) = read_rgb(&data).unwrap();
+        assert_eq!((w, h), (width, height));
+        let decoded: Vec<u8> = decoded.iter().map(|&c| (c * 255.).round() as u8).collect();
+        assert_eq!(decoded, rgb);
+    }
+
+    #[test]
+    fn decodes_all_filter_types() {
+        // zlib level 9 with dynamic Huffman codes, row y using filter y % 5
+        let (w, h, rgb) = read_rgb(include_bytes!("../tests/data/filters.png")).unwrap();
+        assert_eq!((w, h), (16, 10));
+        for y in 0..h {
+            for x in 0..w {
+                let expected = [
+                    (x * 37 + y * 11) % 256,
+                    (x * x + y * 53) % 256,
+                    (x * y * 7 + 90) % 256,
+                ];
+                for (c, &e) in expected.iter().enumerate() {
+                    let got = (rgb[(y * w + x) * 3 + c] * 255.).round() as usize;
+                    assert_eq!(got, e, "pixel ({}, {}) channel {}", x, y, c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_packed_palette() {
+        // 2-bit indices (x + y) % 4 into red, green, blue and white
+        let (w, h, rgb) = read_rgb(include_bytes!("../tests/data/palette.png")).unwrap();
+        assert_eq!((w, h), (5, 3));
+        let palette = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.], [1., 1., 1.]];
+        for y in 0..h {
+            for x in 0..w {
+                let i = (y * w + x) * 3;
+                assert_eq!(rgb[i..i + 3], palette[(x + y) % 4]);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_header() {
+        let data = png(&[(b"IHDR", &ihdr(0x8000_0000, 1, 8, 2)), (b"IEND", &[])]);
+        assert_eq!(read_rgb(&data).unwrap_err(), "image too large");
+
+        // fits the spec limits, but not the size of the image data
+        let data = png(&[
+            (b"IHDR", &ihdr(0x7fff_ffff, 0x7fff_ffff, 16, 6)),
+            (b"IDAT", &zlib_stored(&[0])),
+            (b"IEND", &[]),
+        ]);
+        assert_eq!(read_rgb(&data).unwrap_err(), "image too large");
+    }
+
+    #[test]
+    fn stops_inflating_past_the_expected_size() {
+        // 84 bytes of zlib level 9 expanding to 65536 zeros
+        let mut bomb = vec![
+            0x78, 0xda, 0xed, 0xc1, 0x01, 0x01, 0, 0, 0, 0x80, 0x90, 0xfe, 0xaf, 0xee, 0x08, 0x0a,
+        ];
+        bomb.extend_from_slice(&[0; 63]);
+        bomb.extend_from_slice(&[0x6a, 0, 0x0f, 0, 0x01]);
+        assert_eq!(zlib_inflate(&bomb, 65536).unwrap(), vec![0; 65536]);
+        assert_eq!(zlib_inflate(&bomb, 1000).unwrap_err(), TOO_MUCH_DATA);
+
+        let stored = zlib_stored(&[0; 100]);
+        assert_eq!(zlib_inflate(&stored, 99).unwrap_err(), TOO_MUCH_DATA);
+
+        let data = png(&[
+            (b"IHDR", &ihdr(1, 1, 8, 2)),
+            (b"IDAT", &bomb),
+            (b"IEND", &[]),
+        ]);
+        assert_eq!(read_rgb(&data).unwrap_err(), TOO_MUCH_DATA);
+    }
+}
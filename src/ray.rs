@@ -4,11 +4,12 @@ use crate::vec3::Vec3;
 pub struct Ray {
     a: Vec3,
     b: Vec3,
+    time: f32,
 }
 
 impl Ray {
-    pub fn new(a: Vec3, b: Vec3) -> Ray {
-        Ray { a, b }
+    pub fn new(a: Vec3, b: Vec3, time: f32) -> Ray {
+        Ray { a, b, time }
     }
     pub fn origin(&self) -> Vec3 {
         self.a
@@ -18,6 +19,11 @@ impl Ray {
         self.b
     }
 
+    /// Moment within the shutter interval at which the ray was sent.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
     pub fn point_at_parameter(&self, t: f32) -> Vec3 {
         self.a + self.b * t
     }
@@ -0,0 +1,184 @@
+use crate::aabb::Aabb;
+use crate::hitable::{HitRecord, HitResult, Hitable, HitableList};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// Rectangle lying in the plane `axis = k`, spanning `[a0, a1] x [b0, b1]`
+/// on the two remaining axes taken in x, y, z order.
+#[derive(Clone, Copy, Debug)]
+struct AxisRect {
+    axis: usize,
+    a0: f32,
+    a1: f32,
+    b0: f32,
+    b1: f32,
+    k: f32,
+    /// Whether the outward normal points towards the negative axis.
+    flipped: bool,
+}
+
+impl AxisRect {
+    fn other_axes(&self) -> (usize, usize) {
+        match self.axis {
+            0 => (1, 2),
+            1 => (0, 2),
+            _ => (0, 1),
+        }
+    }
+
+    fn point(&self, axis_value: f32, a: f32, b: f32) -> Vec3 {
+        let mut p = [0.; 3];
+        let (ia, ib) = self.other_axes();
+        p[self.axis] = axis_value;
+        p[ia] = a;
+        p[ib] = b;
+        Vec3::new(p[0], p[1], p[2])
+    }
+
+    fn hit<'a>(&self, r: Ray, t_min: f32, t_max: f32, mat: &'a dyn Material) -> HitResult<'a> {
+        let (ia, ib) = self.other_axes();
+        let t = (self.k - r.origin()[self.axis]) / r.direction()[self.axis];
+        if !(t > t_min && t < t_max) {
+            return None;
+        }
+        let a = r.origin()[ia] + t * r.direction()[ia];
+        let b = r.origin()[ib] + t * r.direction()[ib];
+        if a < self.a0 || a > self.a1 || b < self.b0 || b > self.b1 {
+            return None;
+        }
+        let sign = if self.flipped { -1. } else { 1. };
+        let mut rec = HitRecord::new(r, t, self.point(sign, 0., 0.), mat);
+        rec.u = (a - self.a0) / (self.a1 - self.a0);
+        rec.v = (b - self.b0) / (self.b1 - self.b0);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // pad so that the box never has zero thickness
+        Aabb::new(
+            self.point(self.k - 1e-4, self.a0, self.b0),
+            self.point(self.k + 1e-4, self.a1, self.b1),
+        )
+    }
+}
+
+macro_rules! axis_rect {
+    ($(#[$doc:meta])* $name:ident, $axis:expr, $a0:ident, $a1:ident, $b0:ident, $b1:ident) => {
+        $(#[$doc])*
+        pub struct $name<T: Material> {
+            rect: AxisRect,
+            mat: T,
+        }
+
+        impl<T: Material> $name<T> {
+            pub fn new($a0: f32, $a1: f32, $b0: f32, $b1: f32, k: f32, mat: T) -> Self {
+                Self {
+                    rect: AxisRect {
+                        axis: $axis,
+                        a0: $a0,
+                        a1: $a1,
+                        b0: $b0,
+                        b1: $b1,
+                        k,
+                        flipped: false,
+                    },
+                    mat,
+                }
+            }
+
+            /// Makes the outward normal point towards the negative axis
+            /// instead of the positive one.
+            pub fn flip_normal(mut self) -> Self {
+                self.rect.flipped = !self.rect.flipped;
+                self
+            }
+        }
+
+        impl<T: Material> Hitable for $name<T> {
+            fn hit(&self, r: Ray, t_min: f32, t_max: f32, _: &mut Sampler) -> HitResult<'_> {
+                self.rect.hit(r, t_min, t_max, &self.mat)
+            }
+
+            fn bounding_box(&self) -> Option<Aabb> {
+                Some(self.rect.bounding_box())
+            }
+        }
+    };
+}
+
+axis_rect!(
+    /// Rectangle in the plane `z = k`, facing +z.
+    XYRect, 2, x0, x1, y0, y1
+);
+axis_rect!(
+    /// Rectangle in the plane `y = k`, facing +y.
+    XZRect, 1, x0, x1, z0, z1
+);
+axis_rect!(
+    /// Rectangle in the plane `x = k`, facing +x.
+    YZRect, 0, y0, y1, z0, z1
+);
+
+/// Axis-aligned box made of six rectangles with outward facing normals.
+pub struct BoxShape {
+    sides: HitableList,
+    bbox: Aabb,
+}
+
+impl BoxShape {
+    /// `p0` and `p1` are opposite corners with `p0` the minimum one.
+    pub fn new<T: Material + 'static>(p0: Vec3, p1: Vec3, mat: T) -> Self {
+        let mat = Arc::new(mat);
+        let mut sides = HitableList::new();
+        sides.add(Arc::new(XYRect::new(
+            p0.x,
+            p1.x,
+            p0.y,
+            p1.y,
+            p1.z,
+            mat.clone(),
+        )));
+        sides.add(Arc::new(
+            XYRect::new(p0.x, p1.x, p0.y, p1.y, p0.z, mat.clone()).flip_normal(),
+        ));
+        sides.add(Arc::new(XZRect::new(
+            p0.x,
+            p1.x,
+            p0.z,
+            p1.z,
+            p1.y,
+            mat.clone(),
+        )));
+        sides.add(Arc::new(
+            XZRect::new(p0.x, p1.x, p0.z, p1.z, p0.y, mat.clone()).flip_normal(),
+        ));
+        sides.add(Arc::new(YZRect::new(
+            p0.y,
+            p1.y,
+            p0.z,
+            p1.z,
+            p1.x,
+            mat.clone(),
+        )));
+        sides.add(Arc::new(
+            YZRect::new(p0.y, p1.y, p0.z, p1.z, p0.x, mat).flip_normal(),
+        ));
+        BoxShape {
+            sides,
+            bbox: Aabb::new(p0, p1),
+        }
+    }
+}
+
+impl Hitable for BoxShape {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, sampler: &mut Sampler) -> HitResult<'_> {
+        self.sides.hit(r, t_min, t_max, sampler)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
@@ -0,0 +1,202 @@
+use crate::camera::Camera;
+use crate::hitable::Hitable;
+use crate::image::Image;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::vec3::Vec3;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// What a ray sees when it leaves the scene without hitting anything.
+#[derive(Clone, Copy, Debug)]
+pub enum Background {
+    /// White to light blue gradient along the y axis.
+    Sky,
+    Solid(Vec3),
+}
+
+impl Background {
+    fn color(&self, ray: Ray) -> Vec3 {
+        match *self {
+            Background::Sky => {
+                let unit_direction = ray.direction().make_unit_vector();
+                let t = 0.5 * (unit_direction.y + 1.0);
+                Vec3::new(1., 1., 1.) * (1.0 - t) + Vec3::new(0.5, 0.7, 1.0) * t
+            }
+            Background::Solid(col) => col,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    /// Rays per pixel.
+    pub samples: usize,
+    /// Maximum number of bounces per ray.
+    pub max_depth: usize,
+    pub background: Background,
+    /// Number of worker threads. Each one renders whole scanlines.
+    pub threads: usize,
+    /// The same seed always produces the same image.
+    pub seed: u64,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            width: 200,
+            height: 100,
+            samples: 100,
+            max_depth: 50,
+            background: Background::Sky,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            seed: 0,
+        }
+    }
+}
+
+fn color(
+    ray: Ray,
+    world: &dyn Hitable,
+    depth: usize,
+    settings: &RenderSettings,
+    sampler: &mut Sampler,
+) -> Vec3 {
+    if let Some(rec) = world.hit(ray, 0.001, f32::MAX, sampler) {
+        let emitted = rec.mat.emitted(rec);
+        return match rec.mat.scatter(ray, rec, sampler) {
+            Some((attenuation, scattered)) if depth < settings.max_depth => {
+                emitted + attenuation * color(scattered, world, depth + 1, settings, sampler)
+            }
+            _ => emitted,
+        };
+    }
+    settings.background.color(ray)
+}
+
+/// Renders the row `y` counted from the top of the image.
+fn render_row(
+    world: &dyn Hitable,
+    camera: &Camera,
+    settings: &RenderSettings,
+    y: usize,
+) -> Vec<Vec3> {
+    let (nx, ny) = (settings.width, settings.height);
+    let j = ny - 1 - y;
+    (0..nx)
+        .map(|i| {
+            let mut sampler = Sampler::for_pixel(settings.seed, i, y);
+            let mut col = Vec3::new(0., 0., 0.);
+            for _ in 0..settings.samples {
+                let ray = camera.get_ray(
+                    (i as f32 + sampler.next_f32()) / nx as f32,
+                    (j as f32 + sampler.next_f32()) / ny as f32,
+                    &mut sampler,
+                );
+                col += color(ray, world, 0, settings, &mut sampler);
+            }
+            col / settings.samples as f32
+        })
+        .collect()
+}
+
+pub fn render(world: &dyn Hitable, camera: &Camera, settings: &RenderSettings) -> Image {
+    render_with_progress(world, camera, settings, |_| {})
+}
+
+/// Like `render`, calling `progress` with the number of finished rows each
+/// time a row is done. It is called from the worker threads.
+pub fn render_with_progress<F: Fn(usize) + Sync>(
+    world: &dyn Hitable,
+    camera: &Camera,
+    settings: &RenderSettings,
+    progress: F,
+) -> Image {
+    let image = Mutex::new(Image::new(settings.width, settings.height));
+    let next_row = AtomicUsize::new(0);
+    let done_rows = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..settings.threads.max(1) {
+            s.spawn(|| loop {
+                let y = next_row.fetch_add(1, Ordering::Relaxed);
+                if y >= settings.height {
+                    break;
+                }
+                let row = render_row(world, camera, settings, y);
+                let mut image = image.lock().unwrap();
+                for (x, col) in row.into_iter().enumerate() {
+                    image.set(x, y, col);
+                }
+                // counted under the lock so that reports arrive in order
+                progress(done_rows.fetch_add(1, Ordering::Relaxed) + 1);
+            });
+        }
+    });
+    image.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hitable::{HitableList, Sphere};
+    use crate::material::{Dielectric, Lambertian};
+    use std::sync::Arc;
+
+    fn render_tiny(threads: usize, seed: u64) -> Vec<[f32; 3]> {
+        let mut world = HitableList::new();
+        world.add(Arc::new(Sphere::new(
+            Vec3::new(0., 0., -1.),
+            0.5,
+            Lambertian::new(Vec3::new(0.8, 0.3, 0.3)),
+        )));
+        world.add(Arc::new(Sphere::new(
+            Vec3::new(1., 0., -1.),
+            0.5,
+            Dielectric::new(1.5),
+        )));
+        world.add(Arc::new(Sphere::new(
+            Vec3::new(0., -100.5, -1.),
+            100.,
+            Lambertian::new(Vec3::new(0.8, 0.8, 0.)),
+        )));
+        let settings = RenderSettings {
+            width: 16,
+            height: 8,
+            samples: 4,
+            threads,
+            seed,
+            ..RenderSettings::default()
+        };
+        let camera = Camera::new(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 0., -1.),
+            Vec3::new(0., 1., 0.),
+            90.,
+            2.,
+            0.1,
+            1.,
+        );
+        let image = render(&world, &camera, &settings);
+        let mut pixels = Vec::new();
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                let col = image.get(x, y);
+                pixels.push([col.x, col.y, col.z]);
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn same_seed_renders_the_same_image_on_any_number_of_threads() {
+        assert_eq!(render_tiny(1, 42), render_tiny(4, 42));
+    }
+
+    #[test]
+    fn different_seeds_render_different_images() {
+        assert_ne!(render_tiny(1, 42), render_tiny(1, 43));
+    }
+}
@@ -0,0 +1,35 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+/// Random number source threaded through everything that samples, so a
+/// render only depends on its seed.
+pub struct Sampler {
+    rng: Pcg32,
+}
+
+impl Sampler {
+    pub fn new(seed: u64) -> Self {
+        Sampler {
+            rng: Pcg32::seed_from_u64(seed),
+        }
+    }
+
+    /// Gives every pixel its own stream, which keeps the image identical
+    /// however the pixels are spread over threads.
+    pub fn for_pixel(seed: u64, x: usize, y: usize) -> Self {
+        let pixel = ((y as u64) << 32) | x as u64;
+        Self::new(splitmix64(seed ^ splitmix64(pixel)))
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        self.rng.gen()
+    }
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
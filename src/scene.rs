@@ -0,0 +1,822 @@
+//! Line-based scene description format. Each statement is a keyword followed
+//! by `key=value` parameters; `#` starts a comment. Vectors and colors are
+//! written as `x,y,z` without spaces.
+//!
+//! ```text
+//! settings width=400 height=200 samples=100 max_depth=50 background=sky
+//! camera from=13,2,3 at=0,0,0 vfov=20 aperture=0.1 focus=10
+//!
+//! texture checker checker even=0.2,0.3,0.1 odd=0.9,0.9,0.9 scale=10
+//! material ground lambertian albedo=checker
+//! material glass dielectric ior=1.5
+//!
+//! object sphere center=0,-1000,0 radius=1000 material=ground
+//! object sphere center=0,1,0 radius=1 material=glass
+//! ```
+//!
+//! Textures and materials are declared with a name before being used.
+//! Wherever a texture is expected a color can be given instead.
+//!
+//! Statements:
+//!
+//! - `settings`: `width`, `height`, `samples`, `max_depth`, `seed` and
+//!   `background`, either `sky` or a color.
+//! - `camera`: `from`, `at`, `up`, `vfov`, `aperture`, `focus` (defaults to
+//!   the distance between `from` and `at`) and `shutter=time0,time1`.
+//! - `texture <name> <kind>`: `solid color=`, `checker even= odd= scale=`,
+//!   `image path= wrap=repeat|clamp|mirror`, `noise seed= scale=` and
+//!   `marble seed= scale= color=`.
+//! - `material <name> <kind>`: `lambertian albedo=`, `metal albedo= fuzz=`,
+//!   `dielectric ior=`, `light emit=` and `isotropic albedo=`.
+//! - `object <kind> material=<name>`: `sphere center= radius=`,
+//!   `moving_sphere center0= center1= time0= time1= radius=`,
+//!   `triangle v0= v1= v2=`, `xy_rect x0= x1= y0= y1= k=` (likewise `xz_rect`
+//!   and `yz_rect`, all taking `flip=true`), `box min= max=`, `mesh path=`
+//!   for an OBJ file and `medium shape=<kind> density=` filling any of the
+//!   other shapes with fog, its material usually being `isotropic`.
+//!
+//! Any object can also be placed with `scale=`, `rotate_x=`, `rotate_y=`,
+//! `rotate_z=` (in degrees) and `translate=`, applied in that order.
+//!
+//! Shapes must not be empty: radii are positive, and `min`/`max` or
+//! `x0`/`x1` style bounds are given in increasing order.
+//!
+//! Relative paths are resolved against the directory of the scene file.
+
+use crate::bvh::BvhNode;
+use crate::camera::Camera;
+use crate::hitable::{Hitable, HitableList, MovingSphere, Sphere};
+use crate::instance::Instance;
+use crate::material::{Dielectric, DiffuseLight, Isotropic, Lambertian, Material, Metal};
+use crate::medium::ConstantMedium;
+use crate::obj;
+use crate::rect::{BoxShape, XYRect, XZRect, YZRect};
+use crate::render::{Background, RenderSettings};
+use crate::texture::{
+    CheckerTexture, ImageTexture, MarbleTexture, NoiseTexture, SolidColor, Texture, WrapMode,
+};
+use crate::transform::Transform;
+use crate::triangle::Triangle;
+use crate::vec3::Vec3;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    /// An invalid statement at the given 1-based line.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "{}", err),
+            SceneError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(err) => Some(err),
+            SceneError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(err: io::Error) -> Self {
+        SceneError::Io(err)
+    }
+}
+
+/// Camera placement. The aspect ratio is only known once the image size is
+/// final, so the `Camera` itself is built by `Scene::camera`.
+#[derive(Clone, Copy, Debug)]
+struct CameraParams {
+    look_from: Vec3,
+    look_at: Vec3,
+    vup: Vec3,
+    vfov: f32,
+    aperture: f32,
+    focus_dist: f32,
+    time0: f32,
+    time1: f32,
+}
+
+impl Default for CameraParams {
+    fn default() -> Self {
+        CameraParams {
+            look_from: Vec3::new(0., 0., 0.),
+            look_at: Vec3::new(0., 0., -1.),
+            vup: Vec3::new(0., 1., 0.),
+            vfov: 90.,
+            aperture: 0.,
+            focus_dist: 1.,
+            time0: 0.,
+            time1: 0.,
+        }
+    }
+}
+
+pub struct Scene {
+    pub world: Arc<dyn Hitable>,
+    /// May be changed before rendering, e.g. from the command line.
+    pub settings: RenderSettings,
+    camera: CameraParams,
+}
+
+impl Scene {
+    /// Camera matching the aspect ratio of the current `settings`.
+    pub fn camera(&self) -> Camera {
+        let c = &self.camera;
+        Camera::new(
+            c.look_from,
+            c.look_at,
+            c.vup,
+            c.vfov,
+            self.settings.width as f32 / self.settings.height as f32,
+            c.aperture,
+            c.focus_dist,
+        )
+        .with_shutter(c.time0, c.time1)
+    }
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+    let path = path.as_ref();
+    let src = fs::read_to_string(path)?;
+    parse(&src, path.parent().unwrap_or_else(|| Path::new("")))
+}
+
+/// Parses a scene, resolving relative paths against `base_dir`.
+pub fn parse(src: &str, base_dir: &Path) -> Result<Scene, SceneError> {
+    let mut builder = SceneBuilder {
+        base_dir,
+        settings: RenderSettings::default(),
+        camera: CameraParams::default(),
+        textures: HashMap::new(),
+        materials: HashMap::new(),
+        objects: Vec::new(),
+    };
+    for (i, line) in src.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        builder
+            .statement(line)
+            .map_err(|message| SceneError::Parse {
+                line: i + 1,
+                message,
+            })?;
+    }
+    Ok(builder.build())
+}
+
+struct SceneBuilder<'a> {
+    base_dir: &'a Path,
+    settings: RenderSettings,
+    camera: CameraParams,
+    textures: HashMap<String, Arc<dyn Texture>>,
+    materials: HashMap<String, Arc<dyn Material>>,
+    objects: Vec<Arc<dyn Hitable>>,
+}
+
+impl SceneBuilder<'_> {
+    fn statement(&mut self, line: &str) -> Result<(), String> {
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => return Ok(()),
+        };
+        match keyword {
+            "settings" => {
+                let mut params = Params::new(keyword, tokens)?;
+                self.settings(&mut params)?;
+                params.finish()
+            }
+            "camera" => {
+                let mut params = Params::new(keyword, tokens)?;
+                self.camera(&mut params)?;
+                params.finish()
+            }
+            "texture" => {
+                let (name, kind) = declaration(keyword, &mut tokens)?;
+                if self.textures.contains_key(name) {
+                    return Err(format!("texture `{}` is already defined", name));
+                }
+                let mut params = Params::new(kind, tokens)?;
+                let texture = self.texture(kind, &mut params)?;
+                params.finish()?;
+                self.textures.insert(name.to_string(), texture);
+                Ok(())
+            }
+            "material" => {
+                let (name, kind) = declaration(keyword, &mut tokens)?;
+                if self.materials.contains_key(name) {
+                    return Err(format!("material `{}` is already defined", name));
+                }
+                let mut params = Params::new(kind, tokens)?;
+                let material = self.material(kind, &mut params)?;
+                params.finish()?;
+                self.materials.insert(name.to_string(), material);
+                Ok(())
+            }
+            "object" => {
+                let kind = tokens.next().ok_or("object needs a kind")?;
+                let mut params = Params::new(kind, tokens)?;
+                let mat = self.lookup_material(&mut params)?;
+                let object = self.object(kind, &mut params, mat)?;
+                let object: Arc<dyn Hitable> = match placement(&mut params)? {
+                    Some(transform) => Arc::new(Instance::new(object, transform)),
+                    None => object,
+                };
+                params.finish()?;
+                self.objects.push(object);
+                Ok(())
+            }
+            _ => Err(format!("unknown statement `{}`", keyword)),
+        }
+    }
+
+    fn settings(&mut self, params: &mut Params) -> Result<(), String> {
+        let s = &mut self.settings;
+        s.width = params.get("width")?.unwrap_or(s.width);
+        s.height = params.get("height")?.unwrap_or(s.height);
+        s.samples = params.get("samples")?.unwrap_or(s.samples);
+        s.max_depth = params.get("max_depth")?.unwrap_or(s.max_depth);
+        s.seed = params.get("seed")?.unwrap_or(s.seed);
+        if s.width == 0 || s.height == 0 || s.samples == 0 {
+            return Err("width, height and samples must be positive".to_string());
+        }
+        match params.raw("background") {
+            Some("sky") => s.background = Background::Sky,
+            Some(value) => {
+                let col = Vec3::parse_param(value).map_err(|_| {
+                    format!(
+                        "invalid `background`: expected `sky` or a color, got `{}`",
+                        value
+                    )
+                })?;
+                s.background = Background::Solid(col);
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn camera(&mut self, params: &mut Params) -> Result<(), String> {
+        let c = &mut self.camera;
+        c.look_from = params.require("from")?;
+        c.look_at = params.require("at")?;
+        c.vup = params.get("up")?.unwrap_or(c.vup);
+        c.vfov = params.get("vfov")?.unwrap_or(c.vfov);
+        c.aperture = params.get("aperture")?.unwrap_or(0.);
+        c.focus_dist = params
+            .get("focus")?
+            .unwrap_or_else(|| (c.look_from - c.look_at).length());
+        let shutter: Option<Vec<f32>> = params.get("shutter")?;
+        match shutter.as_deref() {
+            Some(&[time0, time1]) => {
+                c.time0 = time0;
+                c.time1 = time1;
+            }
+            Some(_) => return Err("shutter needs two times, like `0,1`".to_string()),
+            None => {}
+        }
+        if (c.look_from - c.look_at).length() == 0. {
+            return Err("camera `from` and `at` must differ".to_string());
+        }
+        let w = (c.look_from - c.look_at).make_unit_vector();
+        if c.vup.cross(w).length() <= 1e-6 * c.vup.length() {
+            return Err("camera `up` must not be parallel to the view direction".to_string());
+        }
+        if c.vfov <= 0. || c.vfov >= 180. {
+            return Err("`vfov` must be between 0 and 180 degrees".to_string());
+        }
+        if c.aperture < 0. || c.focus_dist <= 0. {
+            return Err("`aperture` must not be negative and `focus` must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    fn texture(&self, kind: &str, params: &mut Params) -> Result<Arc<dyn Texture>, String> {
+        Ok(match kind {
+            "solid" => Arc::new(SolidColor::new(params.require("color")?)),
+            "checker" => Arc::new(CheckerTexture::new(
+                self.lookup_texture(params, "even")?,
+                self.lookup_texture(params, "odd")?,
+                params.get("scale")?.unwrap_or(1.),
+            )),
+            "image" => {
+                let wrap = match params.raw("wrap") {
+                    Some("repeat") | None => WrapMode::Repeat,
+                    Some("clamp") => WrapMode::Clamp,
+                    Some("mirror") => WrapMode::Mirror,
+                    Some(other) => return Err(format!("unknown wrap mode `{}`", other)),
+                };
+                let path = self.path(params)?;
+                Arc::new(ImageTexture::load(path, wrap).map_err(|err| err.to_string())?)
+            }
+            "noise" => Arc::new(NoiseTexture::new(
+                params.get("seed")?.unwrap_or(0),
+                params.get("scale")?.unwrap_or(1.),
+            )),
+            "marble" => Arc::new(MarbleTexture::new(
+                params.get("seed")?.unwrap_or(0),
+                params.get("scale")?.unwrap_or(1.),
+                params
+                    .get("color")?
+                    .unwrap_or_else(|| Vec3::new(1., 1., 1.)),
+            )),
+            _ => return Err(format!("unknown texture kind `{}`", kind)),
+        })
+    }
+
+    fn material(&self, kind: &str, params: &mut Params) -> Result<Arc<dyn Material>, String> {
+        Ok(match kind {
+            "lambertian" => Arc::new(Lambertian::textured(self.lookup_texture(params, "albedo")?)),
+            "metal" => Arc::new(Metal::textured(
+                self.lookup_texture(params, "albedo")?,
+                params.get("fuzz")?.unwrap_or(0.),
+            )),
+            "dielectric" => Arc::new(Dielectric::new(positive(params, "ior")?)),
+            "light" => Arc::new(DiffuseLight::textured(self.lookup_texture(params, "emit")?)),
+            "isotropic" => Arc::new(Isotropic::textured(self.lookup_texture(params, "albedo")?)),
+            _ => return Err(format!("unknown material kind `{}`", kind)),
+        })
+    }
+
+    fn object(
+        &self,
+        kind: &str,
+        params: &mut Params,
+        mat: Arc<dyn Material>,
+    ) -> Result<Arc<dyn Hitable>, String> {
+        Ok(match kind {
+            "sphere" => Arc::new(Sphere::new(
+                params.require("center")?,
+                positive(params, "radius")?,
+                mat,
+            )),
+            "moving_sphere" => Arc::new(MovingSphere::new(
+                params.require("center0")?,
+                params.require("center1")?,
+                params.get("time0")?.unwrap_or(0.),
+                params.get("time1")?.unwrap_or(1.),
+                positive(params, "radius")?,
+                mat,
+            )),
+            "triangle" => Arc::new(Triangle::new(
+                params.require("v0")?,
+                params.require("v1")?,
+                params.require("v2")?,
+                mat,
+            )),
+            "xy_rect" => {
+                let (x0, x1) = interval(params, "x0", "x1")?;
+                let (y0, y1) = interval(params, "y0", "y1")?;
+                let rect = XYRect::new(x0, x1, y0, y1, params.require("k")?, mat);
+                if params.get("flip")?.unwrap_or(false) {
+                    Arc::new(rect.flip_normal())
+                } else {
+                    Arc::new(rect)
+                }
+            }
+            "xz_rect" => {
+                let (x0, x1) = interval(params, "x0", "x1")?;
+                let (z0, z1) = interval(params, "z0", "z1")?;
+                let rect = XZRect::new(x0, x1, z0, z1, params.require("k")?, mat);
+                if params.get("flip")?.unwrap_or(false) {
+                    Arc::new(rect.flip_normal())
+                } else {
+                    Arc::new(rect)
+                }
+            }
+            "yz_rect" => {
+                let (y0, y1) = interval(params, "y0", "y1")?;
+                let (z0, z1) = interval(params, "z0", "z1")?;
+                let rect = YZRect::new(y0, y1, z0, z1, params.require("k")?, mat);
+                if params.get("flip")?.unwrap_or(false) {
+                    Arc::new(rect.flip_normal())
+                } else {
+                    Arc::new(rect)
+                }
+            }
+            "box" => {
+                let min: Vec3 = params.require("min")?;
+                let max: Vec3 = params.require("max")?;
+                if min.x >= max.x || min.y >= max.y || min.z >= max.z {
+                    return Err("box `min` must be less than `max` on every axis".to_string());
+                }
+                Arc::new(BoxShape::new(min, max, mat))
+            }
+            "mesh" => {
+                let path = self.path(params)?;
+                let groups = obj::load(&path)
+                    .map_err(|err| format!("cannot load {}: {}", path.display(), err))?;
+                Arc::new(obj::into_hitable(groups, mat))
+            }
+            "medium" => {
+                let shape = params.raw("shape").ok_or("missing parameter `shape`")?;
+                if shape == "medium" {
+                    return Err("a medium cannot be the shape of another one".to_string());
+                }
+                let density: f32 = params.require("density")?;
+                if density <= 0. {
+                    return Err("density must be positive".to_string());
+                }
+                // the boundary only delimits the volume, its material is never used
+                let boundary = self.object(shape, params, mat.clone())?;
+                Arc::new(ConstantMedium::new(boundary, density, mat))
+            }
+            _ => return Err(format!("unknown object kind `{}`", kind)),
+        })
+    }
+
+    fn lookup_texture(&self, params: &mut Params, key: &str) -> Result<Arc<dyn Texture>, String> {
+        let value = params
+            .raw(key)
+            .ok_or_else(|| format!("missing parameter `{}`", key))?;
+        if let Some(texture) = self.textures.get(value) {
+            return Ok(texture.clone());
+        }
+        match Vec3::parse_param(value) {
+            Ok(color) => Ok(Arc::new(SolidColor::new(color))),
+            Err(_) => Err(format!(
+                "`{}` is neither a color nor a known texture",
+                value
+            )),
+        }
+    }
+
+    fn lookup_material(&self, params: &mut Params) -> Result<Arc<dyn Material>, String> {
+        let name = params
+            .raw("material")
+            .ok_or("missing parameter `material`")?;
+        self.materials
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("unknown material `{}`", name))
+    }
+
+    fn path(&self, params: &mut Params) -> Result<PathBuf, String> {
+        let path = params.raw("path").ok_or("missing parameter `path`")?;
+        Ok(self.base_dir.join(path))
+    }
+
+    fn build(self) -> Scene {
+        let world: Arc<dyn Hitable> = if !self.objects.is_empty()
+            && self.objects.iter().all(|o| o.bounding_box().is_some())
+        {
+            Arc::new(BvhNode::new(self.objects))
+        } else {
+            Arc::new(HitableList::from(self.objects))
+        };
+        Scene {
+            world,
+            settings: self.settings,
+            camera: self.camera,
+        }
+    }
+}
+
+/// Splits the `<name> <kind>` of a texture or material statement.
+fn declaration<'a, I: Iterator<Item = &'a str>>(
+    keyword: &str,
+    tokens: &mut I,
+) -> Result<(&'a str, &'a str), String> {
+    match (tokens.next(), tokens.next()) {
+        (Some(name), Some(kind)) if !name.contains('=') && !kind.contains('=') => Ok((name, kind)),
+        _ => Err(format!("{} needs a name and a kind", keyword)),
+    }
+}
+
+fn positive(params: &mut Params, key: &str) -> Result<f32, String> {
+    let value: f32 = params.require(key)?;
+    if value > 0. {
+        Ok(value)
+    } else {
+        Err(format!("`{}` must be positive", key))
+    }
+}
+
+/// Bounds of a rectangle side, which must not be empty.
+fn interval(params: &mut Params, lo: &str, hi: &str) -> Result<(f32, f32), String> {
+    let (a, b): (f32, f32) = (params.require(lo)?, params.require(hi)?);
+    if a < b {
+        Ok((a, b))
+    } else {
+        Err(format!("`{}` must be less than `{}`", lo, hi))
+    }
+}
+
+/// Transform given by the placement parameters, if any.
+fn placement(params: &mut Params) -> Result<Option<Transform>, String> {
+    let mut transform = None;
+    let mut then = |t: Transform| {
+        transform = Some(transform.map_or(t, |prev| t * prev));
+    };
+    if let Some(factors) = params.get::<Vec3>("scale")? {
+        if factors.x == 0. || factors.y == 0. || factors.z == 0. {
+            return Err("scale factors must not be zero".to_string());
+        }
+        then(Transform::scale(factors));
+    }
+    if let Some(degrees) = params.get("rotate_x")? {
+        then(Transform::rotate_x(degrees));
+    }
+    if let Some(degrees) = params.get("rotate_y")? {
+        then(Transform::rotate_y(degrees));
+    }
+    if let Some(degrees) = params.get("rotate_z")? {
+        then(Transform::rotate_z(degrees));
+    }
+    if let Some(offset) = params.get("translate")? {
+        then(Transform::translate(offset));
+    }
+    Ok(transform)
+}
+
+/// `key=value` parameters of one statement. Every one must be used, so
+/// misspelled keys are reported instead of silently ignored.
+struct Params<'a> {
+    kind: &'a str,
+    values: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Params<'a> {
+    fn new<I: Iterator<Item = &'a str>>(kind: &'a str, tokens: I) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        for token in tokens {
+            let (key, value) = token
+                .split_once('=')
+                .ok_or_else(|| format!("expected `key=value`, got `{}`", token))?;
+            if values.insert(key, value).is_some() {
+                return Err(format!("parameter `{}` is given twice", key));
+            }
+        }
+        Ok(Params { kind, values })
+    }
+
+    fn raw(&mut self, key: &str) -> Option<&'a str> {
+        self.values.remove(key)
+    }
+
+    fn get<T: ParamValue>(&mut self, key: &str) -> Result<Option<T>, String> {
+        self.raw(key)
+            .map(|value| {
+                T::parse_param(value).map_err(|expected| {
+                    format!("invalid `{}`: expected {}, got `{}`", key, expected, value)
+                })
+            })
+            .transpose()
+    }
+
+    fn require<T: ParamValue>(&mut self, key: &str) -> Result<T, String> {
+        self.get(key)?
+            .ok_or_else(|| format!("missing parameter `{}`", key))
+    }
+
+    fn finish(self) -> Result<(), String> {
+        let mut unknown: Vec<&str> = self.values.keys().copied().collect();
+        unknown.sort_unstable();
+        match unknown.first() {
+            Some(key) => Err(format!("unknown parameter `{}` for {}", key, self.kind)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Values of parameters. The error describes what was expected.
+trait ParamValue: Sized {
+    fn parse_param(value: &str) -> Result<Self, String>;
+}
+
+impl ParamValue for f32 {
+    fn parse_param(value: &str) -> Result<Self, String> {
+        value.parse().map_err(|_| "a number".to_string())
+    }
+}
+
+impl ParamValue for usize {
+    fn parse_param(value: &str) -> Result<Self, String> {
+        value.parse().map_err(|_| "a whole number".to_string())
+    }
+}
+
+impl ParamValue for u64 {
+    fn parse_param(value: &str) -> Result<Self, String> {
+        value.parse().map_err(|_| "a whole number".to_string())
+    }
+}
+
+impl ParamValue for bool {
+    fn parse_param(value: &str) -> Result<Self, String> {
+        value.parse().map_err(|_| "`true` or `false`".to_string())
+    }
+}
+
+impl ParamValue for Vec<f32> {
+    fn parse_param(value: &str) -> Result<Self, String> {
+        value
+            .split(',')
+            .map(f32::parse_param)
+            .collect::<Result<_, _>>()
+            .map_err(|_| "comma separated numbers".to_string())
+    }
+}
+
+impl ParamValue for Vec3 {
+    fn parse_param(value: &str) -> Result<Self, String> {
+        match Vec::<f32>::parse_param(value).as_deref() {
+            Ok(&[x, y, z]) => Ok(Vec3::new(x, y, z)),
+            _ => Err("three numbers like `1,0.5,0`".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ray::Ray;
+    use crate::sampler::Sampler;
+
+    fn parse_str(src: &str) -> Result<Scene, SceneError> {
+        parse(src, Path::new(""))
+    }
+
+    fn parse_error(src: &str) -> (usize, String) {
+        match parse_str(src) {
+            Err(SceneError::Parse { line, message }) => (line, message),
+            Err(err) => panic!("unexpected error {}", err),
+            Ok(_) => panic!("scene parsed"),
+        }
+    }
+
+    #[test]
+    fn parses_scene() {
+        let scene = parse_str(
+            "# comment\n\
+             settings width=40 height=20 samples=3 background=0,0,0\n\
+             camera from=0,0,1 at=0,0,-1 vfov=60\n\
+             texture check checker even=1,1,1 odd=0,0,0 scale=2\n\
+             material ground lambertian albedo=check\n\
+             material glass dielectric ior=1.5\n\
+             \n\
+             object sphere center=0,0,-1 radius=0.5 material=glass\n\
+             object box min=-1,-1,-1 max=1,-0.5,1 material=ground rotate_y=30\n",
+        )
+        .unwrap();
+        assert_eq!(scene.settings.width, 40);
+        assert_eq!(scene.settings.height, 20);
+        assert_eq!(scene.settings.samples, 3);
+        assert!(matches!(scene.settings.background, Background::Solid(_)));
+
+        let mut sampler = Sampler::new(0);
+        let r = Ray::new(Vec3::new(0., 0., 1.), Vec3::new(0., 0., -1.), 0.);
+        let rec = scene.world.hit(r, 0.001, f32::MAX, &mut sampler).unwrap();
+        assert!((rec.t - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn medium_alone_is_tested_once_per_ray() {
+        let scene = parse_str(
+            "material fog isotropic albedo=1,1,1\n\
+             object medium shape=box min=0,0,0 max=1,1,1 density=0.5 material=fog\n",
+        )
+        .unwrap();
+        let fog = || Isotropic::new(Vec3::new(1., 1., 1.));
+        let boundary = Arc::new(BoxShape::new(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(1., 1., 1.),
+            fog(),
+        ));
+        let medium = ConstantMedium::new(boundary, 0.5, fog());
+
+        // a world that tests the medium more than once per ray draws more
+        // random numbers and drifts away from the medium on its own
+        let r = Ray::new(Vec3::new(-1., 0.5, 0.5), Vec3::new(1., 0., 0.), 0.);
+        let (mut a, mut b) = (Sampler::new(3), Sampler::new(3));
+        for _ in 0..100 {
+            let in_world = scene.world.hit(r, 0.001, f32::MAX, &mut a).map(|rec| rec.t);
+            let alone = medium.hit(r, 0.001, f32::MAX, &mut b).map(|rec| rec.t);
+            assert_eq!(in_world, alone);
+        }
+    }
+
+    #[test]
+    fn reports_unknown_material() {
+        let (line, message) = parse_error(
+            "material red lambertian albedo=1,0,0\n\
+             object sphere center=0,0,0 radius=1 material=blue\n",
+        );
+        assert_eq!(line, 2);
+        assert_eq!(message, "unknown material `blue`");
+    }
+
+    #[test]
+    fn reports_misspelled_key() {
+        let (line, message) = parse_error(
+            "material red lambertian albedo=1,0,0\n\
+             \n\
+             object sphere center=0,0,0 raduis=1 material=red\n",
+        );
+        assert_eq!(line, 3);
+        assert_eq!(message, "missing parameter `radius`");
+
+        let (line, message) = parse_error("material red lambertian albedo=1,0,0 fuz=0.1\n");
+        assert_eq!(line, 1);
+        assert_eq!(message, "unknown parameter `fuz` for lambertian");
+    }
+
+    #[test]
+    fn reports_duplicate_parameter() {
+        let (line, message) = parse_error("settings width=10 width=20\n");
+        assert_eq!(line, 1);
+        assert_eq!(message, "parameter `width` is given twice");
+    }
+
+    #[test]
+    fn reports_nested_medium() {
+        let (line, message) = parse_error(
+            "material fog isotropic albedo=1,1,1\n\
+             object medium shape=medium density=0.1 material=fog\n",
+        );
+        assert_eq!(line, 2);
+        assert_eq!(message, "a medium cannot be the shape of another one");
+    }
+
+    #[test]
+    fn reports_bad_value() {
+        let (line, message) = parse_error("camera from=0,0 at=0,0,-1\n");
+        assert_eq!(line, 1);
+        assert_eq!(
+            message,
+            "invalid `from`: expected three numbers like `1,0.5,0`, got `0,0`"
+        );
+    }
+
+    #[test]
+    fn reports_degenerate_camera() {
+        for (src, message) in [
+            (
+                "camera from=0,5,0 at=0,0,0",
+                "camera `up` must not be parallel to the view direction",
+            ),
+            (
+                "camera from=0,0,1 at=0,0,0 vfov=180",
+                "`vfov` must be between 0 and 180 degrees",
+            ),
+            (
+                "camera from=0,0,1 at=0,0,0 vfov=0",
+                "`vfov` must be between 0 and 180 degrees",
+            ),
+        ] {
+            assert_eq!(parse_error(src), (1, message.to_string()), "{}", src);
+        }
+    }
+
+    #[test]
+    fn reports_degenerate_shapes() {
+        let materials = "material m lambertian albedo=1,1,1\n\
+                         material glass dielectric ior=1.5\n";
+        for (object, message) in [
+            (
+                "object box min=0,0,0 max=1,-1,1 material=m",
+                "box `min` must be less than `max` on every axis",
+            ),
+            (
+                "object sphere center=0,0,0 radius=0 material=m",
+                "`radius` must be positive",
+            ),
+            (
+                "object sphere center=0,0,0 radius=-1 material=m",
+                "`radius` must be positive",
+            ),
+            (
+                "object xy_rect x0=1 x1=0 y0=0 y1=1 k=0 material=m",
+                "`x0` must be less than `x1`",
+            ),
+            (
+                "object yz_rect y0=0 y1=1 z0=2 z1=2 k=0 material=m",
+                "`z0` must be less than `z1`",
+            ),
+        ] {
+            let src = format!("{}{}\n", materials, object);
+            assert_eq!(parse_error(&src), (3, message.to_string()), "{}", object);
+        }
+        assert_eq!(
+            parse_error("material glass dielectric ior=0\n"),
+            (1, "`ior` must be positive".to_string())
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::image::{Image, ImageError};
+use crate::perlin::Perlin;
+use crate::vec3::Vec3;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Color looked up from surface coordinates `(u, v)` or the hit point `p`.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3;
+}
+
+impl<T: Texture + ?Sized> Texture for Arc<T> {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
+        (**self).value(u, v, p)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SolidColor {
+    color: Vec3,
+}
+
+impl SolidColor {
+    pub fn new(color: Vec3) -> Self {
+        Self { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _: f32, _: f32, _: Vec3) -> Vec3 {
+        self.color
+    }
+}
+
+/// 3D checker pattern of cubes with `scale` long sides.
+pub struct CheckerTexture<T: Texture> {
+    even: T,
+    odd: T,
+    scale: f32,
+}
+
+impl<T: Texture> CheckerTexture<T> {
+    pub fn new(even: T, odd: T, scale: f32) -> Self {
+        Self { even, odd, scale }
+    }
+}
+
+impl<T: Texture> Texture for CheckerTexture<T> {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
+        let cell = |c: f32| (c / self.scale).floor() as i64;
+        if (cell(p.x) + cell(p.y) + cell(p.z)) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// How texture coordinates outside [0, 1] are brought back onto the image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+impl WrapMode {
+    fn apply(self, i: i64, n: usize) -> usize {
+        let n = n as i64;
+        let i = match self {
+            WrapMode::Repeat => i.rem_euclid(n),
+            WrapMode::Clamp => i.clamp(0, n - 1),
+            WrapMode::Mirror => {
+                let i = i.rem_euclid(2 * n);
+                if i < n {
+                    i
+                } else {
+                    2 * n - 1 - i
+                }
+            }
+        };
+        i as usize
+    }
+}
+
+/// Image mapped with `(0, 0)` at its bottom left corner and sampled with
+/// bilinear filtering.
+pub struct ImageTexture {
+    image: Image,
+    wrap: WrapMode,
+}
+
+impl ImageTexture {
+    /// # Panics
+    ///
+    /// Panics if the image has no pixels.
+    pub fn new(image: Image, wrap: WrapMode) -> Self {
+        assert!(
+            image.width() > 0 && image.height() > 0,
+            "ImageTexture needs a non-empty image"
+        );
+        Self { image, wrap }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P, wrap: WrapMode) -> Result<Self, ImageError> {
+        Ok(Self::new(Image::load(path)?, wrap))
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Vec3 {
+        self.image.get(
+            self.wrap.apply(x, self.image.width()),
+            self.wrap.apply(y, self.image.height()),
+        )
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f32, v: f32, _: Vec3) -> Vec3 {
+        // texel centers sit at half-integer coordinates
+        let x = u * self.image.width() as f32 - 0.5;
+        let y = (1. - v) * self.image.height() as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = self.texel(x0, y0) * (1. - fx) + self.texel(x0.saturating_add(1), y0) * fx;
+        let bottom = self.texel(x0, y0.saturating_add(1)) * (1. - fx)
+            + self.texel(x0.saturating_add(1), y0.saturating_add(1)) * fx;
+        top * (1. - fy) + bottom * fy
+    }
+}
+
+/// Gray Perlin noise, `scale` setting its frequency.
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f32,
+}
+
+impl NoiseTexture {
+    pub fn new(seed: u64, scale: f32) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _: f32, _: f32, p: Vec3) -> Vec3 {
+        Vec3::new(1., 1., 1.) * (0.5 * (1. + self.noise.noise(p * self.scale)))
+    }
+}
+
+/// Marble-like veins along z, made by phase shifting a sine with turbulence.
+pub struct MarbleTexture {
+    noise: Perlin,
+    scale: f32,
+    color: Vec3,
+}
+
+impl MarbleTexture {
+    pub fn new(seed: u64, scale: f32, color: Vec3) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            color,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _: f32, _: f32, p: Vec3) -> Vec3 {
+        let phase = self.scale * p.z + 10. * self.noise.turb(p, 7);
+        self.color * (0.5 * (1. + phase.sin()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(
+            (a - b).length() < 1e-5,
+            "({}, {}, {}) != ({}, {}, {})",
+            a.x,
+            a.y,
+            a.z,
+            b.x,
+            b.y,
+            b.z
+        );
+    }
+
+    #[test]
+    fn wraps_indices() {
+        let repeat: Vec<usize> = (-5..9).map(|i| WrapMode::Repeat.apply(i, 4)).collect();
+        assert_eq!(repeat, [3, 0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3, 0]);
+        let clamp: Vec<usize> = (-5..9).map(|i| WrapMode::Clamp.apply(i, 4)).collect();
+        assert_eq!(clamp, [0, 0, 0, 0, 0, 0, 1, 2, 3, 3, 3, 3, 3, 3]);
+        let mirror: Vec<usize> = (-5..9).map(|i| WrapMode::Mirror.apply(i, 4)).collect();
+        assert_eq!(mirror, [3, 3, 2, 1, 0, 0, 1, 2, 3, 3, 2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn samples_bilinearly() {
+        let black = Vec3::new(0., 0., 0.);
+        let white = Vec3::new(1., 1., 1.);
+        let gray = Vec3::new(0.5, 0.5, 0.5);
+        let mut image = Image::new(2, 1);
+        image.set(1, 0, white);
+        let p = Vec3::new(0., 0., 0.);
+
+        let clamp = ImageTexture::new(image.clone(), WrapMode::Clamp);
+        // texel centers, halfway between them, and the clamped edges
+        assert_close(clamp.value(0.25, 0.5, p), black);
+        assert_close(clamp.value(0.75, 0.5, p), white);
+        assert_close(clamp.value(0.5, 0.5, p), gray);
+        assert_close(clamp.value(0.375, 0.5, p), Vec3::new(0.25, 0.25, 0.25));
+        assert_close(clamp.value(0., 0.5, p), black);
+        assert_close(clamp.value(1., 0.5, p), white);
+
+        // across the edge the repeated image blends its last and first columns
+        let repeat = ImageTexture::new(image, WrapMode::Repeat);
+        assert_close(repeat.value(0., 0.5, p), gray);
+        assert_close(repeat.value(1., 0.5, p), gray);
+        assert_close(repeat.value(1.25, 0.5, p), black);
+    }
+
+    #[test]
+    fn maps_v_from_the_bottom_row() {
+        let red = Vec3::new(1., 0., 0.);
+        let blue = Vec3::new(0., 0., 1.);
+        let mut image = Image::new(1, 2);
+        image.set(0, 0, red);
+        image.set(0, 1, blue);
+        let texture = ImageTexture::new(image, WrapMode::Clamp);
+        let p = Vec3::new(0., 0., 0.);
+        assert_close(texture.value(0.5, 0.75, p), red);
+        assert_close(texture.value(0.5, 0.25, p), blue);
+        assert_close(texture.value(0.5, 0.5, p), (red + blue) * 0.5);
+    }
+}
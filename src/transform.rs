@@ -0,0 +1,255 @@
+use crate::aabb::Aabb;
+use crate::vec3::Vec3;
+use std::f32::consts::PI;
+use std::ops;
+
+type Matrix = [[f32; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1., 0., 0., 0.],
+    [0., 1., 0., 0.],
+    [0., 0., 1., 0.],
+    [0., 0., 0., 1.],
+];
+
+/// Affine 4x4 transform. The inverse is kept alongside the matrix, so it is
+/// never computed numerically.
+///
+/// `a * b` applies `b` first, then `a`.
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    m: Matrix,
+    inv: Matrix,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            m: IDENTITY,
+            inv: IDENTITY,
+        }
+    }
+
+    pub fn translate(offset: Vec3) -> Self {
+        let translation = |d: Vec3| {
+            let mut m = IDENTITY;
+            m[0][3] = d.x;
+            m[1][3] = d.y;
+            m[2][3] = d.z;
+            m
+        };
+        Transform {
+            m: translation(offset),
+            inv: translation(-offset),
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if a factor is zero.
+    pub fn scale(factors: Vec3) -> Self {
+        assert!(
+            factors.x != 0. && factors.y != 0. && factors.z != 0.,
+            "cannot scale by zero"
+        );
+        let scaling = |s: Vec3| {
+            let mut m = IDENTITY;
+            m[0][0] = s.x;
+            m[1][1] = s.y;
+            m[2][2] = s.z;
+            m
+        };
+        Transform {
+            m: scaling(factors),
+            inv: scaling(Vec3::new(1., 1., 1.) / factors),
+        }
+    }
+
+    /// Counterclockwise rotation around `axis` when looking against it.
+    pub fn rotate(axis: Vec3, degrees: f32) -> Self {
+        let a = axis.make_unit_vector();
+        let theta = degrees * PI / 180.;
+        let (sin, cos) = theta.sin_cos();
+        let t = 1. - cos;
+        let m = [
+            [
+                t * a.x * a.x + cos,
+                t * a.x * a.y - sin * a.z,
+                t * a.x * a.z + sin * a.y,
+                0.,
+            ],
+            [
+                t * a.x * a.y + sin * a.z,
+                t * a.y * a.y + cos,
+                t * a.y * a.z - sin * a.x,
+                0.,
+            ],
+            [
+                t * a.x * a.z - sin * a.y,
+                t * a.y * a.z + sin * a.x,
+                t * a.z * a.z + cos,
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ];
+        // rotations are orthogonal
+        Transform {
+            m,
+            inv: transpose(&m),
+        }
+    }
+
+    pub fn rotate_x(degrees: f32) -> Self {
+        Self::rotate(Vec3::new(1., 0., 0.), degrees)
+    }
+
+    pub fn rotate_y(degrees: f32) -> Self {
+        Self::rotate(Vec3::new(0., 1., 0.), degrees)
+    }
+
+    pub fn rotate_z(degrees: f32) -> Self {
+        Self::rotate(Vec3::new(0., 0., 1.), degrees)
+    }
+
+    pub fn inverse(&self) -> Self {
+        Transform {
+            m: self.inv,
+            inv: self.m,
+        }
+    }
+
+    pub fn point(&self, p: Vec3) -> Vec3 {
+        apply(&self.m, p, 1.)
+    }
+
+    pub fn vector(&self, v: Vec3) -> Vec3 {
+        apply(&self.m, v, 0.)
+    }
+
+    /// Transforms a surface normal with the inverse transpose, so it stays
+    /// perpendicular to the surface. The result is not normalized.
+    pub fn normal(&self, n: Vec3) -> Vec3 {
+        apply(&transpose(&self.inv), n, 0.)
+    }
+
+    /// Box enclosing the eight transformed corners of `bbox`.
+    pub fn bounding_box(&self, bbox: Aabb) -> Aabb {
+        let (lo, hi) = (bbox.min(), bbox.max());
+        let corner = |i: usize| {
+            let pick = |bit: usize, l: f32, h: f32| if i & bit == 0 { l } else { h };
+            let p = self.point(Vec3::new(
+                pick(1, lo.x, hi.x),
+                pick(2, lo.y, hi.y),
+                pick(4, lo.z, hi.z),
+            ));
+            Aabb::new(p, p)
+        };
+        (1..8).fold(corner(0), |acc, i| acc.surrounding(corner(i)))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl ops::Mul for Transform {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Transform {
+            m: multiply(&self.m, &other.m),
+            inv: multiply(&other.inv, &self.inv),
+        }
+    }
+}
+
+fn apply(m: &Matrix, v: Vec3, w: f32) -> Vec3 {
+    let row = |r: &[f32; 4]| r[0] * v.x + r[1] * v.y + r[2] * v.z + r[3] * w;
+    Vec3::new(row(&m[0]), row(&m[1]), row(&m[2]))
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut m = [[0.; 4]; 4];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, c) in row.iter_mut().enumerate() {
+            *c = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+fn transpose(a: &Matrix) -> Matrix {
+    let mut m = [[0.; 4]; 4];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, c) in row.iter_mut().enumerate() {
+            *c = a[j][i];
+        }
+    }
+    m
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(
+            (a - b).length() < 1e-5,
+            "({}, {}, {}) != ({}, {}, {})",
+            a.x,
+            a.y,
+            a.z,
+            b.x,
+            b.y,
+            b.z
+        );
+    }
+
+    #[test]
+    fn composes_right_to_left() {
+        let t = Transform::translate(Vec3::new(1., 2., 3.))
+            * Transform::rotate_z(90.)
+            * Transform::scale(Vec3::new(2., 1., 1.));
+        // scaled to (2, 0, 0), rotated to (0, 2, 0), then translated
+        assert_close(t.point(Vec3::new(1., 0., 0.)), Vec3::new(1., 4., 3.));
+        assert_close(t.vector(Vec3::new(1., 0., 0.)), Vec3::new(0., 2., 0.));
+    }
+
+    #[test]
+    fn inverts_products_in_reverse_order() {
+        let a = Transform::translate(Vec3::new(1., 2., 3.));
+        let b = Transform::rotate_x(30.);
+        let c = Transform::scale(Vec3::new(2., -1., 0.5));
+        let t = a * b * c;
+        let by_parts = c.inverse() * b.inverse() * a.inverse();
+        for p in [
+            Vec3::new(0., 0., 0.),
+            Vec3::new(1., -2., 3.),
+            Vec3::new(-4., 5., 0.5),
+        ] {
+            assert_close(t.inverse().point(p), by_parts.point(p));
+            assert_close(t.inverse().point(t.point(p)), p);
+        }
+    }
+
+    #[test]
+    fn keeps_normals_perpendicular_under_non_uniform_scale() {
+        // the plane x + y = 1 becomes x / 2 + y = 1
+        let t = Transform::scale(Vec3::new(2., 1., 1.));
+        let n = t.normal(Vec3::new(1., 1., 0.));
+        assert_close(
+            n.make_unit_vector(),
+            Vec3::new(1., 2., 0.).make_unit_vector(),
+        );
+        let tangent = t.vector(Vec3::new(1., -1., 0.));
+        assert!(n.dot(tangent).abs() < 1e-6);
+    }
+
+    #[test]
+    fn flips_normals_under_negative_scale() {
+        let t = Transform::scale(Vec3::new(-1., 1., 1.));
+        assert_close(t.normal(Vec3::new(1., 0., 0.)), Vec3::new(-1., 0., 0.));
+        assert_close(t.normal(Vec3::new(0., 1., 0.)), Vec3::new(0., 1., 0.));
+    }
+}
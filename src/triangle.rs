@@ -0,0 +1,238 @@
+use crate::aabb::Aabb;
+use crate::bvh::BvhNode;
+use crate::hitable::{HitRecord, HitResult, Hitable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::sampler::Sampler;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+pub struct Triangle<T: Material> {
+    vertices: [Vec3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[(f32, f32); 3]>,
+    mat: T,
+}
+
+impl<T: Material> Triangle<T> {
+    pub fn new(v0: Vec3, v1: Vec3, v2: Vec3, mat: T) -> Self {
+        Self {
+            vertices: [v0, v1, v2],
+            normals: None,
+            uvs: None,
+            mat,
+        }
+    }
+
+    /// Interpolates these per-vertex normals instead of using the flat face
+    /// normal.
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [(f32, f32); 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+}
+
+impl<T: Material> Hitable for Triangle<T> {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, _: &mut Sampler) -> HitResult<'_> {
+        let (t, b1, b2) = intersect(r, self.vertices, t_min, t_max)?;
+        Some(shade(
+            r,
+            t,
+            (b1, b2),
+            self.vertices,
+            self.normals,
+            self.uvs,
+            &self.mat,
+        ))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(bounds(self.vertices))
+    }
+}
+
+/// Möller–Trumbore intersection. Returns `t` and the barycentric coordinates
+/// of `v1` and `v2`.
+fn intersect(r: Ray, v: [Vec3; 3], t_min: f32, t_max: f32) -> Option<(f32, f32, f32)> {
+    let edge1 = v[1] - v[0];
+    let edge2 = v[2] - v[0];
+    let pvec = r.direction().cross(edge2);
+    let det = edge1.dot(pvec);
+    if det.abs() < 1e-8 {
+        return None;
+    }
+    let inv_det = 1. / det;
+    let tvec = r.origin() - v[0];
+    let b1 = tvec.dot(pvec) * inv_det;
+    if !(0. ..=1.).contains(&b1) {
+        return None;
+    }
+    let qvec = tvec.cross(edge1);
+    let b2 = r.direction().dot(qvec) * inv_det;
+    if b2 < 0. || b1 + b2 > 1. {
+        return None;
+    }
+    let t = edge2.dot(qvec) * inv_det;
+    if t < t_max && t > t_min {
+        Some((t, b1, b2))
+    } else {
+        None
+    }
+}
+
+fn shade<'a>(
+    r: Ray,
+    t: f32,
+    (b1, b2): (f32, f32),
+    v: [Vec3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[(f32, f32); 3]>,
+    mat: &'a dyn Material,
+) -> HitRecord<'a> {
+    let b0 = 1. - b1 - b2;
+    let outward_normal = (v[1] - v[0]).cross(v[2] - v[0]).make_unit_vector();
+    let mut rec = HitRecord::new(r, t, outward_normal, mat);
+    if let Some(n) = normals {
+        // the winding decides which side is the front, the vertex normals only
+        // smooth the shading
+        let shading = (n[0] * b0 + n[1] * b1 + n[2] * b2).make_unit_vector();
+        rec.normal = if rec.front_face { shading } else { -shading };
+    }
+    let (u, v) = match uvs {
+        Some(uv) => (
+            uv[0].0 * b0 + uv[1].0 * b1 + uv[2].0 * b2,
+            uv[0].1 * b0 + uv[1].1 * b1 + uv[2].1 * b2,
+        ),
+        None => (b1, b2),
+    };
+    rec.u = u;
+    rec.v = v;
+    rec
+}
+
+fn bounds(v: [Vec3; 3]) -> Aabb {
+    // pad flat triangles so that the box never has zero thickness
+    let pad = Vec3::new(1e-4, 1e-4, 1e-4);
+    let b = Aabb::new(v[0], v[0])
+        .surrounding(Aabb::new(v[1], v[1]))
+        .surrounding(Aabb::new(v[2], v[2]));
+    Aabb::new(b.min() - pad, b.max() + pad)
+}
+
+/// Indices of one triangle into the buffers of `MeshData`.
+#[derive(Clone, Copy, Debug)]
+pub struct Face {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+}
+
+/// Vertex buffers shared by all triangles of a mesh.
+#[derive(Clone, Debug, Default)]
+pub struct MeshData {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f32, f32)>,
+    pub faces: Vec<Face>,
+}
+
+/// Triangle mesh with its own BVH over the faces.
+pub struct Mesh {
+    bvh: Option<BvhNode>,
+}
+
+impl Mesh {
+    /// # Panics
+    ///
+    /// Panics if a face refers to a vertex that is not in `data`.
+    pub fn new<T: Material + 'static>(data: Arc<MeshData>, mat: T) -> Self {
+        for face in &data.faces {
+            let in_range = |indices: Option<[usize; 3]>, len: usize| {
+                indices.is_none_or(|i| i.iter().all(|&i| i < len))
+            };
+            assert!(
+                in_range(Some(face.positions), data.positions.len())
+                    && in_range(face.normals, data.normals.len())
+                    && in_range(face.uvs, data.uvs.len()),
+                "mesh face {:?} is out of range",
+                face
+            );
+        }
+        let mat = Arc::new(mat);
+        let triangles: Vec<Arc<dyn Hitable>> = (0..data.faces.len())
+            .map(|face| {
+                Arc::new(MeshTriangle {
+                    data: data.clone(),
+                    face,
+                    mat: mat.clone(),
+                }) as Arc<dyn Hitable>
+            })
+            .collect();
+        Mesh {
+            bvh: if triangles.is_empty() {
+                None
+            } else {
+                Some(BvhNode::new(triangles))
+            },
+        }
+    }
+}
+
+impl Hitable for Mesh {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, sampler: &mut Sampler) -> HitResult<'_> {
+        self.bvh.as_ref()?.hit(r, t_min, t_max, sampler)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.as_ref()?.bounding_box()
+    }
+}
+
+struct MeshTriangle<T: Material> {
+    data: Arc<MeshData>,
+    face: usize,
+    mat: Arc<T>,
+}
+
+impl<T: Material> MeshTriangle<T> {
+    fn vertices(&self) -> [Vec3; 3] {
+        let p = self.data.faces[self.face].positions;
+        [
+            self.data.positions[p[0]],
+            self.data.positions[p[1]],
+            self.data.positions[p[2]],
+        ]
+    }
+}
+
+impl<T: Material> Hitable for MeshTriangle<T> {
+    fn hit(&self, r: Ray, t_min: f32, t_max: f32, _: &mut Sampler) -> HitResult<'_> {
+        let vertices = self.vertices();
+        let (t, b1, b2) = intersect(r, vertices, t_min, t_max)?;
+        let face = &self.data.faces[self.face];
+        let normals = face.normals.map(|n| {
+            [
+                self.data.normals[n[0]],
+                self.data.normals[n[1]],
+                self.data.normals[n[2]],
+            ]
+        });
+        let uvs = face.uvs.map(|uv| {
+            [
+                self.data.uvs[uv[0]],
+                self.data.uvs[uv[1]],
+                self.data.uvs[uv[2]],
+            ]
+        });
+        Some(shade(r, t, (b1, b2), vertices, normals, uvs, &*self.mat))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(bounds(self.vertices()))
+    }
+}